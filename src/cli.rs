@@ -1,5 +1,9 @@
-use super::cmd::{RsysCmd, RsysOpt};
-use rsys::{Result, Rsys};
+use super::{
+    cmd::{RsysCmd, RsysOpt, UnitOpts},
+    util::set_units,
+};
+use anyhow::Result;
+use rsys::Rsys;
 use structopt::StructOpt;
 
 pub struct RsysCli {
@@ -24,13 +28,27 @@ impl RsysCli {
     pub fn main(&self) -> Result<()> {
         if let Some(cmd) = self.opts.cmd.clone() {
             match cmd {
-                RsysCmd::Get(opts) => self.get(opts)?,
-                RsysCmd::Dump(opts) => self.dump(opts)?,
+                RsysCmd::Get(opts) => {
+                    apply_units(&opts.units);
+                    self.get(opts)?
+                }
+                RsysCmd::Dump(opts) => {
+                    apply_units(&opts.units);
+                    self.dump(opts)?
+                }
+                // Watch only prints JSON with raw numbers
                 RsysCmd::Watch(opts) => self.watch(opts)?,
-                RsysCmd::Show { cmd } => self.show(cmd),
+                RsysCmd::Show { cmd, units } => {
+                    apply_units(&units);
+                    self.show(cmd)?
+                }
             }
         }
 
         Ok(())
     }
 }
+
+fn apply_units(units: &UnitOpts) {
+    set_units(units.system, units.bits);
+}
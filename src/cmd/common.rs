@@ -1,11 +1,28 @@
-use crate::util::{conv_b, conv_hz, handle_err};
+use crate::{
+    linux::{
+        block::{block_attributes, BlockAttributes, BlockTree, SECTOR_SIZE},
+        conntrack::Conntrack,
+        link::{link_details, LinkDetails, CLASS_NET_DIR},
+        loadavg::LoadAvg,
+        md::MdArrays,
+        neighbor::Neighbors,
+        netstat::NetStat,
+        pressure::SystemPressure,
+        rapl::RaplPowers,
+        read_trimmed,
+        route::Routes,
+        wireless::WirelessInterfaces,
+        SYSFS,
+    },
+    util::{conv_b, conv_hz, glob_match, handle_err},
+};
 use prettytable::{format, Table};
 use rsys::{
     linux::{
         cpu::Processor,
         mem::Memory,
         misc::MountPoints,
-        net::Interfaces,
+        net::{Interface, Interfaces},
         ps::Processes,
         storage::{
             storage_devices, DeviceMapper, DeviceMappers, MultipleDeviceStorage, MultipleDeviceStorages, StorageDevice,
@@ -15,9 +32,57 @@ use rsys::{
     Result, Rsys,
 };
 use serde::{Deserialize, Serialize};
-use std::fmt::{self, Formatter};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Formatter},
+    path::Path,
+};
+use structopt::StructOpt;
+
+#[derive(Debug, Default, StructOpt, Clone)]
+// Selects network interfaces by name. Busy hosts with many containers have hundreds
+// of veth interfaces that bury the interesting ones. Not a doc comment so that it
+// doesn't replace help of commands it's flattened into.
+pub struct IfaceFilter {
+    #[structopt(long = "iface", number_of_values = 1)]
+    /// Only include interfaces matching a glob pattern like `eth*`. Can be passed multiple times
+    pub include: Vec<String>,
+    #[structopt(long = "exclude-iface", number_of_values = 1)]
+    /// Skip interfaces matching a glob pattern like `veth*` or `docker*`. Can be passed multiple times
+    pub exclude: Vec<String>,
+    #[structopt(long)]
+    /// Hide interfaces that are down or have never carried traffic, that is haven't received
+    /// nor transmitted anything since they were created
+    pub hide_idle: bool,
+}
+impl IfaceFilter {
+    /// Filter that only accepts interfaces matching `pattern`
+    pub fn with_pattern(pattern: &str) -> IfaceFilter {
+        IfaceFilter {
+            include: vec![pattern.to_string()],
+            ..IfaceFilter::default()
+        }
+    }
+
+    /// Checks only include and exclude patterns against `name`
+    pub fn matches(&self, name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| glob_match(p, name)))
+            && !self.exclude.iter().any(|p| glob_match(p, name))
+    }
 
-const SECTOR_SIZE: u64 = 512;
+    /// Checks patterns against name of `iface` and with `hide_idle` whether it's idle
+    /// reading its state from sysfs mounted at `sysfs`
+    pub fn accepts<P: AsRef<Path>>(&self, iface: &Interface, sysfs: P) -> bool {
+        self.matches(&iface.name) && !(self.hide_idle && IfaceFilter::is_idle(iface, sysfs.as_ref()))
+    }
+
+    // Idle means down or never carried traffic. Counters are totals since the interface was
+    // created so one that was busy before going quiet is still shown.
+    fn is_idle(iface: &Interface, sysfs: &Path) -> bool {
+        let operstate = read_trimmed(sysfs.join(CLASS_NET_DIR).join(&iface.name).join("operstate"));
+        operstate.map(|s| s == "down").unwrap_or(false) || iface.stat.rx_bytes + iface.stat.tx_bytes == 0
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SystemInfo {
@@ -30,6 +95,8 @@ pub struct SystemInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     uptime: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    load: Option<LoadAvg>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     os: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     kernel: Option<String>,
@@ -38,41 +105,88 @@ pub struct SystemInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     memory: Option<Memory>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pressure: Option<SystemPressure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    power: Option<RaplPowers>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     processes: Option<Processes>,
     #[serde(skip_serializing_if = "Option::is_none")]
     mounts: Option<MountPoints>,
     #[serde(skip_serializing_if = "Option::is_none")]
     interfaces: Option<Interfaces>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    netstat: Option<NetStat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    links: Option<BTreeMap<String, LinkDetails>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    routes: Option<Routes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    neighbors: Option<Neighbors>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conntrack: Option<Conntrack>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wireless: Option<WirelessInterfaces>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     storage_devices: Option<StorageDevices>,
     #[serde(skip_serializing_if = "Option::is_none")]
     multiple_device_storages: Option<MultipleDeviceStorages>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    md_arrays: Option<MdArrays>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     device_mappers: Option<DeviceMappers>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    storage_attributes: Option<BTreeMap<String, BlockAttributes>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    storage_tree: Option<BlockTree>,
     #[serde(skip_serializing)]
     display_stats: bool,
     #[serde(skip_serializing)]
     display_all: bool,
 }
+
+#[derive(Debug, Default, Clone, Copy)]
+/// Sections of system information to read
+pub struct InfoSections {
+    pub arch: bool,
+    pub hostname: bool,
+    pub domain: bool,
+    pub uptime: bool,
+    pub load: bool,
+    pub os: bool,
+    pub kernel: bool,
+    pub cpu: bool,
+    pub memory: bool,
+    pub net: bool,
+    pub storage: bool,
+    pub mounts: bool,
+    pub processes: bool,
+    pub pressure: bool,
+    /// Read all sections
+    pub all: bool,
+    /// Include statistics of storage devices and network interfaces
+    pub stats: bool,
+}
+
 impl SystemInfo {
-    #[allow(clippy::too_many_arguments)]
-    pub fn new(
-        r: &Rsys,
-        arch: bool,
-        hostname: bool,
-        domain: bool,
-        uptime: bool,
-        os: bool,
-        kernel: bool,
-        cpu: bool,
-        memory: bool,
-        net: bool,
-        storage: bool,
-        mounts: bool,
-        all: bool,
-        stats: bool,
-        processes: bool,
-    ) -> Result<SystemInfo> {
+    pub fn new(r: &Rsys, sections: InfoSections) -> Result<SystemInfo> {
+        let InfoSections {
+            arch,
+            hostname,
+            domain,
+            uptime,
+            load,
+            os,
+            kernel,
+            cpu,
+            memory,
+            net,
+            storage,
+            mounts,
+            processes,
+            pressure,
+            all,
+            stats,
+        } = sections;
         Ok(Self {
             arch: if arch || all { Some(handle_err(r.arch())) } else { None },
             hostname: if hostname || all {
@@ -90,6 +204,11 @@ impl SystemInfo {
             } else {
                 None
             },
+            load: if load || all {
+                Some(handle_err(LoadAvg::read()))
+            } else {
+                None
+            },
             os: if os || all { Some(r.os()) } else { None },
             kernel: if kernel || all {
                 Some(handle_err(r.kernel_version()))
@@ -106,6 +225,13 @@ impl SystemInfo {
             } else {
                 None
             },
+            // Pressure is only included when the kernel supports it
+            pressure: if pressure || all {
+                SystemPressure::read().ok()
+            } else {
+                None
+            },
+            power: None,
             processes: if processes || all {
                 Some(handle_err(r.processes()))
             } else {
@@ -117,6 +243,19 @@ impl SystemInfo {
                 None
             },
             interfaces: if net || all { Some(handle_err(r.ifaces())) } else { None },
+            netstat: if net || all { NetStat::read().ok() } else { None },
+            links: if net || all { Some(link_details(SYSFS)) } else { None },
+            routes: if net || all { Routes::read().ok() } else { None },
+            neighbors: if net || all { Neighbors::read().ok() } else { None },
+            // Missing when the `nf_conntrack` module is not loaded. Per protocol counts are added
+            // with `with_conntrack_protocols` as they require reading the whole table.
+            conntrack: if net || all { Conntrack::read().ok() } else { None },
+            // Only included on hosts with wireless interfaces
+            wireless: if net || all {
+                WirelessInterfaces::read(SYSFS).ok().filter(|w| !w.0.is_empty())
+            } else {
+                None
+            },
             storage_devices: if storage || all {
                 let show_stats = if all { true } else { stats };
                 Some(handle_err(storage_devices::<StorageDevice>(show_stats)))
@@ -129,16 +268,69 @@ impl SystemInfo {
             } else {
                 None
             },
+            // /proc/mdstat is missing if md driver is not loaded which is not an error
+            md_arrays: if storage || all {
+                MdArrays::read(SYSFS).ok()
+            } else {
+                None
+            },
             device_mappers: if storage || all {
                 let show_stats = if all { true } else { stats };
                 Some(handle_err(storage_devices::<DeviceMapper>(show_stats)))
             } else {
                 None
             },
+            storage_attributes: if storage || all {
+                Some(block_attributes(SYSFS))
+            } else {
+                None
+            },
+            storage_tree: None,
             display_stats: stats,
             display_all: all,
         })
     }
+    /// Adds power consumption which needs two samples of energy counters
+    /// so it can't be read together with the rest of information
+    pub fn with_power(mut self, power: Option<RaplPowers>) -> Self {
+        self.power = power;
+        self
+    }
+    /// Adds topology of block devices linking disks with their partitions,
+    /// device mappers and md arrays
+    pub fn with_storage_tree(mut self, tree: Option<BlockTree>) -> Self {
+        self.storage_tree = tree;
+        self
+    }
+    /// Adds number of connection tracking entries of each protocol. Requires reading the whole
+    /// table which is largest when it's close to full so it's only done when `enabled`.
+    pub fn with_conntrack_protocols(mut self, enabled: bool) -> Self {
+        if enabled {
+            self.conntrack = self.conntrack.map(Conntrack::with_protocols);
+        }
+        self
+    }
+    /// Drops network interfaces rejected by `filter` together with their links,
+    /// routes and neighbors
+    pub fn with_iface_filter(mut self, filter: &IfaceFilter) -> Self {
+        if let Some(ifaces) = &mut self.interfaces {
+            ifaces.0.retain(|iface| filter.accepts(iface, SYSFS));
+            let names = ifaces.0.iter().map(|iface| iface.name.as_str()).collect::<Vec<&str>>();
+            if let Some(links) = &mut self.links {
+                links.retain(|name, _| names.contains(&name.as_str()));
+            }
+        }
+        if let Some(routes) = &mut self.routes {
+            routes.0.retain(|route| filter.matches(&route.iface));
+        }
+        if let Some(neighbors) = &mut self.neighbors {
+            neighbors.0.retain(|neighbor| filter.matches(&neighbor.iface));
+        }
+        if let Some(wireless) = &mut self.wireless {
+            wireless.0.retain(|w| filter.matches(&w.iface));
+        }
+        self
+    }
     fn general_section_string(&self) -> String {
         let mut s = String::new();
         let mut table = Table::new();
@@ -160,6 +352,11 @@ impl SystemInfo {
         if let Some(uptime) = &self.uptime {
             table.add_row(row!["uptime:", l -> format!("{} s", uptime)]);
         }
+        if let Some(load) = &self.load {
+            table.add_row(row!["load:", l -> load.averages_str()]);
+            table.add_row(row!["tasks:", l -> load.tasks_str()]);
+            table.add_row(row!["last pid:", l -> load.last_pid]);
+        }
         if let Some(os) = &self.os {
             table.add_row(row!["os:", l -> os]);
         }
@@ -211,6 +408,20 @@ impl SystemInfo {
         }
         s
     }
+    fn pressure_section_string(&self) -> String {
+        let mut s = String::new();
+        if let Some(pressure) = &self.pressure {
+            s.push_str(&pressure.to_string());
+        }
+        s
+    }
+    fn power_section_string(&self) -> String {
+        let mut s = String::new();
+        if let Some(power) = &self.power {
+            s.push_str(&power.to_string());
+        }
+        s
+    }
     fn network_section_string(&self) -> String {
         let mut s = String::new();
         if let Some(ifaces) = &self.interfaces {
@@ -254,11 +465,67 @@ impl SystemInfo {
                 ]);
             }
             s.push_str(&net_table.to_string());
+            if let Some(links) = &self.links {
+                s.push_str(&self.links_string(links));
+            }
+            if let Some(wireless) = &self.wireless {
+                s.push_str(&wireless.to_string());
+            }
             if self.display_stats || self.display_all {
                 s.push_str(" NETWORK STATS: ( rx / tx - received / transfered )\n");
                 s.push_str(&stats_table.to_string());
             }
         }
+        if let Some(routes) = &self.routes {
+            s.push_str(&routes.to_string());
+        }
+        if let Some(neighbors) = &self.neighbors {
+            s.push_str(&neighbors.to_string());
+        }
+        if let Some(conntrack) = &self.conntrack {
+            s.push_str(&conntrack.to_string());
+        }
+        if let Some(netstat) = &self.netstat {
+            s.push_str(&netstat.to_string());
+        }
+        s
+    }
+    fn links_string(&self, links: &BTreeMap<String, LinkDetails>) -> String {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP);
+        table.add_row(row![
+            c =>
+            "name",
+            "type",
+            "operstate",
+            "carrier",
+            "carrier changes",
+            "duplex",
+            "speed",
+            "driver",
+            "pci address",
+        ]);
+        let opt = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+        for (name, link) in links {
+            table.add_row(row![
+                name,
+                link.kind,
+                link.operstate,
+                c -> opt(link.carrier.map(|c| if c { "yes" } else { "no" }.to_string())),
+                r -> opt(link.carrier_changes.map(|c| c.to_string())),
+                c -> opt(link.duplex.clone()),
+                r -> opt(link.speed.map(|s| format!("{} mb/s", s))),
+                opt(link.driver.clone()),
+                opt(link.pci_address.clone()),
+            ]);
+        }
+        format!(" LINKS:\n{}", table)
+    }
+    fn storage_tree_section_string(&self) -> String {
+        let mut s = String::new();
+        if let Some(tree) = &self.storage_tree {
+            s.push_str(&tree.to_string());
+        }
         s
     }
     fn storage_section_string(&self) -> String {
@@ -337,8 +604,11 @@ impl SystemInfo {
                 let mut mds_table = Table::new();
                 mds_table.set_format(*format::consts::FORMAT_NO_LINESEP);
 
-                mds_table.add_row(row![ c => "name", "size", "major", "min", "block size", "level",]);
+                mds_table.add_row(
+                    row![ c => "name", "size", "major", "min", "block size", "level", "state", "health", "sync",],
+                );
                 for md in mds {
+                    let array = self.md_arrays.as_ref().and_then(|a| a.get(&md.info.dev));
                     mds_table.add_row(row![
                         md.info.dev,
                         r -> conv_b(md.info.size as u64 * SECTOR_SIZE),
@@ -346,6 +616,9 @@ impl SystemInfo {
                         md.info.min,
                         md.info.block_size,
                         md.level,
+                        array.map(|a| a.state.clone()).unwrap_or_default(),
+                        array.map(|a| a.health()).unwrap_or_default(),
+                        array.and_then(|a| a.sync_str()).unwrap_or_default(),
                     ]);
                     if let Some(stat) = &md.info.stat {
                         stats_table.add_row(row![
@@ -368,6 +641,11 @@ impl SystemInfo {
                     }
                 }
                 s.push_str(&mds_table.to_string());
+                if let Some(arrays) = &self.md_arrays {
+                    for array in arrays.degraded() {
+                        s.push_str(&format!(" WARNING: array {} is {}\n", array.name, array.health()));
+                    }
+                }
             }
             if let Some(dms) = &self.device_mappers {
                 s.push_str(" DEVICE MAPPERS:\n");
@@ -407,6 +685,9 @@ impl SystemInfo {
                 }
                 s.push_str(&dms_table.to_string());
             }
+            if let Some(attributes) = &self.storage_attributes {
+                s.push_str(&self.storage_attributes_string(attributes));
+            }
             if self.display_stats || self.display_all {
                 s.push_str(" STORAGE STATS: (r - read, w - write, d - discard)\n");
                 s.push_str(&stats_table.to_string());
@@ -414,6 +695,54 @@ impl SystemInfo {
         }
         s
     }
+    fn storage_attributes_string(&self, attributes: &BTreeMap<String, BlockAttributes>) -> String {
+        let mut names = Vec::new();
+        if let Some(storages) = &self.storage_devices {
+            names.extend(storages.iter().map(|s| s.info.dev.to_string()));
+        }
+        if let Some(mds) = &self.multiple_device_storages {
+            names.extend(mds.iter().map(|md| md.info.dev.to_string()));
+        }
+        if let Some(dms) = &self.device_mappers {
+            names.extend(dms.iter().map(|dm| dm.info.dev.to_string()));
+        }
+
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP);
+        table.add_row(row![
+            c =>
+            "device",
+            "rotational",
+            "scheduler",
+            "log/phy block",
+            "discard",
+            "nr requests",
+            "read ahead",
+            "firmware",
+            "serial",
+        ]);
+        let opt = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+        for name in names {
+            if let Some(a) = attributes.get(&name) {
+                table.add_row(row![
+                    name,
+                    c -> opt(a.rotational.map(|r| r.to_string())),
+                    opt(a.scheduler.clone()),
+                    r -> format!(
+                        "{} / {}",
+                        opt(a.logical_block_size.map(|b| b.to_string())),
+                        opt(a.physical_block_size.map(|b| b.to_string()))
+                    ),
+                    c -> opt(a.discard.map(|d| d.to_string())),
+                    r -> opt(a.nr_requests.map(|n| n.to_string())),
+                    r -> opt(a.read_ahead_kb.map(|r| format!("{} KiB", r))),
+                    opt(a.firmware.clone()),
+                    opt(a.serial.clone()),
+                ]);
+            }
+        }
+        format!(" STORAGE ATTRIBUTES:\n{}", table)
+    }
     fn processes_section_string(&self) -> String {
         let mut s = String::new();
         if let Some(processes) = &self.processes {
@@ -490,7 +819,10 @@ impl fmt::Display for SystemInfo {
         s.push_str(&self.general_section_string());
         s.push_str(&self.cpu_section_string());
         s.push_str(&self.memory_section_string());
+        s.push_str(&self.pressure_section_string());
+        s.push_str(&self.power_section_string());
         s.push_str(&self.network_section_string());
+        s.push_str(&self.storage_tree_section_string());
         s.push_str(&self.storage_section_string());
         s.push_str(&self.processes_section_string());
         write!(f, "{}", s)
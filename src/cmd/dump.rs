@@ -1,10 +1,11 @@
 use super::DumpOpts;
 use crate::{
-    cmd::common::SystemInfo,
-    util::{print, PrintFormat},
+    cmd::common::{InfoSections, SystemInfo},
+    linux::{block::BlockTree, SYSFS},
+    util::{handle_err, print, PrintFormat},
     RsysCli,
 };
-use rsys::Result;
+use anyhow::Result;
 
 impl RsysCli {
     pub fn dump(&self, opts: DumpOpts) -> Result<()> {
@@ -15,24 +16,35 @@ impl RsysCli {
         } else {
             PrintFormat::Normal
         };
+        let storage_tree = if opts.storage || opts.all {
+            Some(handle_err(BlockTree::read(SYSFS)))
+        } else {
+            None
+        };
         print(
             SystemInfo::new(
                 &self.system,
-                true,
-                true,
-                true,
-                true,
-                true,
-                true,
-                opts.cpu,
-                opts.memory,
-                opts.network,
-                opts.storage,
-                opts.mounts,
-                opts.all,
-                opts.stats,
-                opts.processes,
-            )?,
+                InfoSections {
+                    arch: true,
+                    hostname: true,
+                    domain: true,
+                    uptime: true,
+                    load: true,
+                    os: true,
+                    kernel: true,
+                    cpu: opts.cpu,
+                    memory: opts.memory,
+                    net: opts.network,
+                    storage: opts.storage,
+                    mounts: opts.mounts,
+                    processes: opts.processes,
+                    all: opts.all,
+                    stats: opts.stats,
+                    ..InfoSections::default()
+                },
+            )?
+            .with_storage_tree(storage_tree)
+            .with_iface_filter(&opts.iface_filter),
             format,
             opts.pretty,
         )
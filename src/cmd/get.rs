@@ -1,14 +1,37 @@
 use super::GetOpts;
 use crate::{
     cli::RsysCli,
-    util::{print, PrintFormat},
+    linux::{
+        block::{BlockAttributes, BlockDevices, BlockTree},
+        cgroup::Cgroup,
+        conntrack::Conntrack,
+        link::LinkDetails,
+        loadavg::LoadAvg,
+        md::{MdArray, MdArrays},
+        neighbor::Neighbors,
+        netstat::NetStat,
+        ports::ListeningPorts,
+        power_supply::PowerSupplies,
+        pressure::SystemPressure,
+        route::Routes,
+        sensors::Sensors,
+        socket::{SocketState, Sockets},
+        topology::NetTopology,
+        wireless::Wireless,
+    },
+    util::{did_you_mean, print, PrintFormat},
 };
+use anyhow::{anyhow, Result};
 use rsys::linux::{
     net::Interface,
     ps::{processes, Process},
     storage::{BlockStorageDeviceName, DeviceMapper, MultipleDeviceStorage, ScsiCdrom, StorageDevice},
 };
-use rsys::Result;
+use serde::Serialize;
+use std::{
+    fmt::{self, Formatter},
+    path::{Path, PathBuf},
+};
 use structopt::StructOpt;
 
 #[allow(non_camel_case_types)]
@@ -16,6 +39,17 @@ use structopt::StructOpt;
 pub enum Property {
     /// Cpu architecture
     arch,
+    /// Usage and limits of a cgroup v2 group and its descendants
+    cgroup {
+        #[structopt(default_value = "/")]
+        /// Path of the group relative to cgroup root. For example `/system.slice`
+        path: String,
+        #[structopt(long, default_value = "/sys/fs/cgroup", parse(from_os_str))]
+        /// Mountpoint of the cgroup v2 hierarchy
+        root: PathBuf,
+    },
+    /// Connection tracking table entries out of `nf_conntrack_max`, drops and entries per protocol
+    conntrack,
     /// All cpu stats and cores
     cpu,
     cpu_model,
@@ -23,13 +57,19 @@ pub enum Property {
     cpu_cores,
     domain,
     hostname,
-    /// Lookup statistics and information about network interface
+    /// Lookup statistics and information about network interface together with its link state,
+    /// driver, type and signal of wireless interfaces
     interface {
         /// Name of the interface to lookup. For example `eth0` or `enp8s0`
         name: String,
+        #[structopt(long, default_value = "/sys", parse(from_os_str))]
+        /// Mountpoint of sysfs
+        sysfs: PathBuf,
     },
     interfaces,
     kernel,
+    /// Load averages, running and total task count and last pid from /proc/loadavg
+    load,
     logical_cores,
     /// All memory statistics
     memory,
@@ -37,19 +77,76 @@ pub enum Property {
     memory_total,
     /// Mountpoints from /etc/mounts
     mounts,
+    /// IPv4 neighbors with their hardware addresses from the ARP cache
+    neighbors,
+    /// Tree of bridges with their ports, bonds with their slaves, vlans with their parent
+    /// devices and veth peers
+    net_topology {
+        #[structopt(long, default_value = "/sys", parse(from_os_str))]
+        /// Mountpoint of sysfs
+        sysfs: PathBuf,
+    },
+    /// TCP, UDP and IP error and fragmentation counters from /proc/net/snmp and /proc/net/netstat
+    netstat,
     os,
     pid {
         id: i32,
     },
+    /// Listening TCP and bound UDP sockets with pid, name and user of the owning process
+    ports {
+        #[structopt(long)]
+        /// Only list sockets bound to this port
+        port: Option<u16>,
+    },
+    /// AC adapters and batteries from /sys/class/power_supply
+    power {
+        #[structopt(long, default_value = "/sys", parse(from_os_str))]
+        /// Mountpoint of sysfs
+        sysfs: PathBuf,
+    },
+    /// Pressure stall information of cpu, memory and io from /proc/pressure
+    pressure,
     /// Prints the first process that contains name in its cmdline
     ps {
         /// Process name
         name: String,
     },
+    /// IPv4 and IPv6 routes with gateways, flags, metrics and interfaces
+    routes,
+    /// Temperatures, fan speeds, voltages and power readings of hardware sensors
+    sensors {
+        #[structopt(long, default_value = "/sys", parse(from_os_str))]
+        /// Mountpoint of sysfs
+        sysfs: PathBuf,
+    },
+    /// TCP, UDP and unix sockets from /proc/net
+    sockets {
+        #[structopt(long, possible_values = SocketState::VARIANTS)]
+        /// Only list sockets in this state
+        state: Option<SocketState>,
+        #[structopt(long)]
+        /// Only list sockets with this local or remote port
+        port: Option<u16>,
+    },
     /// Storage device info
     storage {
-        /// Name of the storage device. For example `sda` or `md0`
+        /// Name of the storage device. For example `sda`, `sda1`, `nvme0n1` or `md0`
         name: String,
+        #[structopt(long, default_value = "/sys", parse(from_os_str))]
+        /// Mountpoint of sysfs
+        sysfs: PathBuf,
+    },
+    /// All block devices with their kind, including partitions
+    storages {
+        #[structopt(long, default_value = "/sys", parse(from_os_str))]
+        /// Mountpoint of sysfs
+        sysfs: PathBuf,
+    },
+    /// Tree of block devices with their partitions, device mappers and md arrays like `lsblk`
+    storage_tree {
+        #[structopt(long, default_value = "/sys", parse(from_os_str))]
+        /// Mountpoint of sysfs
+        sysfs: PathBuf,
     },
     swap_free,
     swap_total,
@@ -62,28 +159,39 @@ impl RsysCli {
         let format = PrintFormat::from_bools(opts.json, opts.yaml);
         match opts.property {
             arch => print(self.system.arch()?, format, opts.pretty)?,
+            cgroup { path, root } => print(Cgroup::read(&root, &path)?, format, opts.pretty)?,
+            conntrack => print(Conntrack::read()?.with_protocols(), format, opts.pretty)?,
             cpu => print(self.system.processor()?, format, opts.pretty)?,
             cpu_model => print(self.system.cpu()?, format, opts.pretty)?,
             cpu_clock => print(self.system.cpu_clock()?, format, opts.pretty)?,
             cpu_cores => print(self.system.cpu_cores()?, format, opts.pretty)?,
             domain => print(self.system.domainname()?, format, opts.pretty)?,
             hostname => print(self.system.hostname()?, format, opts.pretty)?,
-            interface { name } => {
+            interface { name, sysfs } => {
                 if let Some(iface) = self.get_interface(&name) {
-                    print(iface, format, opts.pretty)?;
+                    let link = LinkDetails::read(&sysfs, &name).ok();
+                    let wireless = Wireless::read(&sysfs, &name).ok();
+                    print(InterfaceDetails { iface, link, wireless }, format, opts.pretty)?;
                 } else {
                     println!("Interface `{}` not found", name);
                 }
             }
             interfaces => print(self.system.ifaces()?, format, opts.pretty)?,
             kernel => print(self.system.kernel_version()?, format, opts.pretty)?,
+            load => print(LoadAvg::read()?, format, opts.pretty)?,
             logical_cores => print(self.system.logical_cores()?, format, opts.pretty)?,
+            neighbors => print(Neighbors::read()?, format, opts.pretty)?,
+            net_topology { sysfs } => print(NetTopology::read(&sysfs)?, format, opts.pretty)?,
+            netstat => print(NetStat::read()?, format, opts.pretty)?,
             os => print(self.system.os(), format, opts.pretty)?,
             memory => print(self.system.memory()?, format, opts.pretty)?,
             memory_free => print(self.system.memory_free()?, format, opts.pretty)?,
             memory_total => print(self.system.memory_total()?, format, opts.pretty)?,
             mounts => print(self.system.mounts()?, format, opts.pretty)?,
             pid { id } => print(Process::new(id)?, format, opts.pretty)?,
+            ports { port } => print(ListeningPorts::read()?.filter(port), format, opts.pretty)?,
+            power { sysfs } => print(PowerSupplies::read(&sysfs)?, format, opts.pretty)?,
+            pressure => print(SystemPressure::read()?, format, opts.pretty)?,
             ps { name } => {
                 for process in processes()? {
                     if process.cmdline.contains(&name) {
@@ -92,7 +200,12 @@ impl RsysCli {
                     }
                 }
             }
-            storage { name } => self.print_storage(&name, format, opts.pretty)?,
+            routes => print(Routes::read()?, format, opts.pretty)?,
+            sensors { sysfs } => print(Sensors::read(&sysfs)?, format, opts.pretty)?,
+            sockets { state, port } => print(Sockets::read()?.filter(state, port), format, opts.pretty)?,
+            storage { name, sysfs } => self.print_storage(&name, &sysfs, format, opts.pretty)?,
+            storages { sysfs } => print(BlockDevices::read(&sysfs)?, format, opts.pretty)?,
+            storage_tree { sysfs } => print(BlockTree::read(&sysfs)?, format, opts.pretty)?,
             swap_total => print(self.system.swap_total()?, format, opts.pretty)?,
             swap_free => print(self.system.swap_free()?, format, opts.pretty)?,
             uptime => print(self.system.uptime()?, format, opts.pretty)?,
@@ -100,17 +213,39 @@ impl RsysCli {
         Ok(())
     }
 
-    fn print_storage(&self, name: &str, format: PrintFormat, pretty: bool) -> Result<()> {
-        if name.starts_with(StorageDevice::prefix()) {
-            print(self.system.stat_block_device(name)?, format, pretty)?
-        } else if name.starts_with(DeviceMapper::prefix()) {
-            print(self.system.stat_device_mapper(name)?, format, pretty)?
-        } else if name.starts_with(MultipleDeviceStorage::prefix()) {
-            print(self.system.stat_multiple_device_storage(name)?, format, pretty)?
-        } else if name.starts_with(ScsiCdrom::prefix()) {
-            print(self.system.stat_scsi_cdrom(name)?, format, pretty)?
+    fn print_storage(&self, name: &str, sysfs: &Path, format: PrintFormat, pretty: bool) -> Result<()> {
+        let devices = BlockDevices::read(sysfs)?;
+        let device =
+            devices
+                .get(name)
+                .ok_or_else(|| match did_you_mean(name, devices.0.iter().map(|d| d.name.as_str())) {
+                    Some(similar) => anyhow!("Storage device `{}` not found, did you mean `{}`?", name, similar),
+                    None => anyhow!(
+                        "Storage device `{}` not found. Use `get storages` to list available devices",
+                        name
+                    ),
+                })?;
+
+        // Partitions share the prefix of their disk but rsys can only stat whole devices
+        if device.parent.is_none() {
+            let attributes = device.attributes.clone();
+            if name.starts_with(StorageDevice::prefix()) {
+                let storage = self.system.stat_block_device(name)?;
+                return Ok(print(StorageDetails::new(storage, attributes), format, pretty)?);
+            } else if name.starts_with(DeviceMapper::prefix()) {
+                let storage = self.system.stat_device_mapper(name)?;
+                return Ok(print(StorageDetails::new(storage, attributes), format, pretty)?);
+            } else if name.starts_with(MultipleDeviceStorage::prefix()) {
+                let mut details = StorageDetails::new(self.system.stat_multiple_device_storage(name)?, attributes);
+                details.health = MdArrays::read(sysfs).ok().and_then(|a| a.get(name).cloned());
+                return Ok(print(details, format, pretty)?);
+            } else if name.starts_with(ScsiCdrom::prefix()) {
+                let storage = self.system.stat_scsi_cdrom(name)?;
+                return Ok(print(StorageDetails::new(storage, attributes), format, pretty)?);
+            }
         }
 
+        print(device.clone(), format, pretty)?;
         Ok(())
     }
 
@@ -121,3 +256,59 @@ impl RsysCli {
         None
     }
 }
+
+#[derive(Debug, Serialize)]
+// Storage info from rsys together with queue and hardware attributes from sysfs
+// and health of the array for md devices
+struct StorageDetails<T> {
+    #[serde(flatten)]
+    storage: T,
+    attributes: BlockAttributes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    health: Option<MdArray>,
+}
+
+impl<T> StorageDetails<T> {
+    fn new(storage: T, attributes: BlockAttributes) -> Self {
+        StorageDetails {
+            storage,
+            attributes,
+            health: None,
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for StorageDetails<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.storage, self.attributes)?;
+        if let Some(health) = &self.health {
+            write!(f, "{}", health)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+// Interface info from rsys together with link details from sysfs and signal
+// of wireless interfaces
+struct InterfaceDetails {
+    #[serde(flatten)]
+    iface: Interface,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    link: Option<LinkDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wireless: Option<Wireless>,
+}
+
+impl fmt::Display for InterfaceDetails {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.iface)?;
+        if let Some(link) = &self.link {
+            write!(f, "{}", link)?;
+        }
+        if let Some(wireless) = &self.wireless {
+            write!(f, "{}", wireless)?;
+        }
+        Ok(())
+    }
+}
@@ -3,6 +3,8 @@ pub mod dump;
 pub mod get;
 pub mod show;
 pub mod watch;
+use crate::util::UnitSystem;
+use common::IfaceFilter;
 use get::Property;
 pub use show::ShowCmd;
 use structopt::StructOpt;
@@ -13,7 +15,8 @@ pub enum RsysCmd {
     Get(GetOpts),
     /// Dumps all information
     Dump(DumpOpts),
-    /// Monitor specified parameters. Default parameters are hostname and uptime.
+    /// Monitor specified parameters. Default parameters are hostname, uptime, load average
+    /// and pressure stall information if the kernel supports it.
     /// To monitor more parameters use flags like `cpu`, `memory` or `storage`.
     /// This command runs indefinitely unless a `duration` parameter is specified
     /// and by default prints JSON with parameters each second. To change how often
@@ -24,9 +27,21 @@ pub enum RsysCmd {
         #[structopt(subcommand)]
         /// What dashboard to show
         cmd: ShowCmd,
+        #[structopt(flatten)]
+        units: UnitOpts,
     },
 }
 
+#[derive(StructOpt, Clone)]
+pub struct UnitOpts {
+    #[structopt(long = "units", default_value = "si", possible_values = UnitSystem::VARIANTS)]
+    /// Prefixes of byte units, `si` for powers of 1000 like MB or `iec` for powers of 1024 like MiB
+    pub system: UnitSystem,
+    #[structopt(long)]
+    /// Display network transfer rates in bits per second to compare them with link speeds
+    pub bits: bool,
+}
+
 #[derive(StructOpt, Clone)]
 #[structopt(name = "rsys", about = "Aquire all important information about your system")]
 pub struct RsysOpt {
@@ -49,6 +64,8 @@ pub struct GetOpts {
     #[structopt(short, long)]
     /// Make the output pretty
     pub pretty: bool,
+    #[structopt(flatten)]
+    pub units: UnitOpts,
 }
 
 #[derive(StructOpt, Clone)]
@@ -69,11 +86,14 @@ pub struct DumpOpts {
     /// Include memory statistics
     pub memory: bool,
     #[structopt(long)]
-    /// Adds network interfaces to the output
+    /// Adds network interfaces with their link state and wireless signal, routes, neighbors,
+    /// connection tracking usage and protocol counters to the output
     pub network: bool,
+    #[structopt(flatten)]
+    pub iface_filter: IfaceFilter,
     #[structopt(long)]
     /// Adds info about storage devices, device mappers,
-    /// multiple device arrays
+    /// multiple device arrays and a tree linking them together
     pub storage: bool,
     #[structopt(long)]
     /// Adds all processes
@@ -88,6 +108,8 @@ pub struct DumpOpts {
     #[structopt(short, long)]
     /// Shortcut for `--cpu --memory --storage --network --mounts --stats --processes`
     pub all: bool,
+    #[structopt(flatten)]
+    pub units: UnitOpts,
 }
 
 #[derive(StructOpt, Clone)]
@@ -102,9 +124,16 @@ pub struct WatchOpts {
     /// Include memory statistics
     pub memory: bool,
     #[structopt(long)]
-    /// Adds network interfaces to the output
+    /// Adds network interfaces with their link state and wireless signal, routes, neighbors,
+    /// connection tracking usage and protocol counters to the output
     pub network: bool,
     #[structopt(long)]
+    /// Adds connection tracking entries of each protocol. Reads the whole table on every run
+    /// so it's costly on busy hosts. Only functional with `--network` flag
+    pub conntrack_protocols: bool,
+    #[structopt(flatten)]
+    pub iface_filter: IfaceFilter,
+    #[structopt(long)]
     /// Adds info about storage devices, device mappers,
     /// multiple device arrays
     pub storage: bool,
@@ -112,8 +141,11 @@ pub struct WatchOpts {
     /// Whether to parse stats for all storage devices or just the main ones.
     /// Only functional with `--storage` flag
     pub stats: bool,
+    #[structopt(long)]
+    /// Adds power consumption of cpu packages, cores and dram from RAPL counters
+    pub power: bool,
     #[structopt(short, long)]
-    /// Shortcut for `--cpu --memory --storage --network --mounts`
+    /// Shortcut for `--cpu --memory --storage --network --mounts --power`
     pub all: bool,
     #[structopt(short, long)]
     /// Duration in seconds for which to collect data. Default is 18_446_744_073_709_551_615 seconds
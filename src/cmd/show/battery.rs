@@ -0,0 +1,182 @@
+use super::{
+    common::{
+        kv_span, single_widget_loop, spans_from, DataSeries, GraphSettings, GraphWidget, InfoGraphWidget, Monitor,
+        Screen, StatefulWidget, Statistic,
+    },
+    cpu::CoreUsageStat,
+    events::Config,
+};
+use crate::{
+    linux::power_supply::{fmt_duration, PowerSupplies, PowerSupply},
+    util::{conv_p, conv_t, random_color},
+};
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Span, Spans},
+    widgets::{Dataset, Paragraph},
+    Frame,
+};
+
+const X_AXIS: (f64, f64) = (0., 60.0);
+const Y_AXIS: (f64, f64) = (0., 100.);
+const TICK_RATE: u64 = 1000;
+
+#[derive(Debug)]
+// Capacity of a battery or online state of other power supplies
+pub struct BatteryStat {
+    supply: PowerSupply,
+    data: DataSeries,
+}
+impl From<PowerSupply> for BatteryStat {
+    fn from(supply: PowerSupply) -> Self {
+        Self {
+            supply,
+            data: DataSeries::new(random_color(Some(20))),
+        }
+    }
+}
+impl Statistic for BatteryStat {
+    fn update(&mut self, m: &mut Screen) -> Result<()> {
+        self.supply
+            .update()
+            .map_err(|e| anyhow!("Failed to update power supply `{}` - {}", self.supply.name, e))?;
+        if let Some(capacity) = self.supply.capacity {
+            self.data.add(m.elapsed_since_start(), capacity as f64);
+        }
+
+        Ok(())
+    }
+    fn pop(&mut self) -> f64 {
+        let removed = self.data.pop();
+        if let Some(point) = self.data.first() {
+            return point.0 - removed.0;
+        }
+        0.
+    }
+    fn name(&self) -> &str {
+        &self.supply.name
+    }
+}
+impl BatteryStat {
+    fn info(&self) -> Vec<Spans> {
+        let s = &self.supply;
+        let mut spans = vec![Spans::from(Span::styled(
+            format!("{} ({})", s.name, s.kind),
+            Style::default().add_modifier(Modifier::BOLD).fg(Color::Green),
+        ))];
+        let mut kv = |k: &str, v: Option<String>| {
+            if let Some(v) = v {
+                spans.push(spans_from(vec![kv_span(k.to_string(), v, self.data.color, true)]));
+            }
+        };
+        kv(
+            " online   : ",
+            s.online.map(|o| if o { "yes" } else { "no" }.to_string()),
+        );
+        kv(" status   : ", s.status.clone());
+        kv(" capacity : ", s.capacity.map(|c| conv_p(c as f64)));
+        kv(" power    : ", s.power_watts().map(|p| format!("{:.2} W", p)));
+        kv(" remaining: ", s.time_remaining.map(fmt_duration));
+        kv(" health   : ", s.health.clone());
+        kv(" cycles   : ", s.cycle_count.map(|c| c.to_string()));
+        spans
+    }
+}
+
+impl GraphWidget for Monitor<BatteryStat> {
+    fn datasets(&self) -> Vec<Dataset> {
+        self.stats
+            .iter()
+            .filter(|s| s.supply.is_battery())
+            .map(|s| {
+                Dataset::default()
+                    .name(s.name())
+                    .marker(symbols::Marker::Braille)
+                    .style(Style::default().fg(s.data.color))
+                    .data(&s.data.dataset())
+            })
+            .collect()
+    }
+    fn settings(&self) -> GraphSettings {
+        GraphSettings::new()
+            .title("Battery", Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan))
+            .x_title("Time", Style::default().fg(Color::White))
+            .y_title("Capacity", Style::default().fg(Color::White))
+            .x_labels(self.m.x_bounds_labels(conv_t, 4))
+            .y_labels(self.m.y_bounds_labels(conv_p, 4))
+    }
+    fn monitor(&self) -> &Screen {
+        &self.m
+    }
+}
+
+impl InfoGraphWidget for Monitor<BatteryStat> {
+    const DIRECTION: Direction = Direction::Horizontal;
+    const CONSTRAINTS: [Constraint; 2] = [Constraint::Percentage(20), Constraint::Min(80)];
+
+    fn render_extra_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let spans = self.stats.iter().flat_map(|s| s.info()).collect::<Vec<Spans>>();
+        f.render_widget(Paragraph::new(spans), area);
+    }
+}
+
+impl Monitor<BatteryStat> {
+    pub fn new(sysfs: &Path) -> Result<Monitor<BatteryStat>> {
+        let mut stats = PowerSupplies::read(sysfs)?
+            .0
+            .into_iter()
+            .map(BatteryStat::from)
+            .collect::<Vec<BatteryStat>>();
+        if !stats.iter().any(|s| s.supply.is_battery()) {
+            return Err(anyhow!("No battery found in `{}`", sysfs.display()));
+        }
+        // Batteries first as they are the ones graphed
+        stats.sort_by_key(|s| !s.supply.is_battery());
+
+        Ok(Monitor {
+            stats,
+            m: Screen::new(X_AXIS, Y_AXIS),
+        })
+    }
+}
+
+/// Battery capacity graphed next to cpu usage to see how load drains it
+pub struct BatteryMonitor {
+    battery: Monitor<BatteryStat>,
+    cpu: Monitor<CoreUsageStat>,
+}
+
+impl StatefulWidget for BatteryMonitor {
+    fn update(&mut self) -> Result<()> {
+        StatefulWidget::update(&mut self.battery)?;
+        StatefulWidget::update(&mut self.cpu)
+    }
+    fn render_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+
+        StatefulWidget::render_widget(&self.battery, f, chunks[0]);
+        StatefulWidget::render_widget(&self.cpu, f, chunks[1]);
+    }
+}
+
+impl BatteryMonitor {
+    pub fn new(sysfs: &Path) -> Result<BatteryMonitor> {
+        Ok(BatteryMonitor {
+            battery: Monitor::<BatteryStat>::new(sysfs)?,
+            cpu: Monitor::<CoreUsageStat>::new()?,
+        })
+    }
+
+    pub fn graph_loop(sysfs: &Path) -> Result<()> {
+        let mut monitor = BatteryMonitor::new(sysfs)?;
+        single_widget_loop(&mut monitor, Config::new(TICK_RATE))
+    }
+}
@@ -0,0 +1,192 @@
+use super::{
+    common::{single_widget_loop, Screen, StatefulWidget},
+    events::Config,
+};
+use crate::{
+    linux::cgroup::Cgroup,
+    util::{conv_b, conv_fbs, conv_p},
+};
+use anyhow::Result;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Row, Table},
+    Frame,
+};
+
+const CGROUP_HEADERS: &[&str] = &[
+    "cgroup", "cpu", "memory", "limit", "io r/s", "io w/s", "pids", "cpu psi", "mem psi", "io psi",
+];
+// Ratio of memory.current to memory.max above which a group is highlighted
+const MEMORY_WARN_RATIO: f64 = 0.9;
+
+#[derive(Debug, Default, Clone, Copy)]
+// Counters needed to calculate rates between updates
+struct CgroupCounters {
+    cpu_usec: u64,
+    rbytes: u64,
+    wbytes: u64,
+}
+
+#[derive(Debug)]
+// Usage of a single cgroup as displayed in a row of the table
+struct CgroupUsage {
+    name: String,
+    cpu: Option<f64>,
+    memory: Option<u64>,
+    memory_max: Option<u64>,
+    read_speed: f64,
+    write_speed: f64,
+    pids: Option<u64>,
+    pids_max: Option<u64>,
+    cpu_psi: Option<f64>,
+    memory_psi: Option<f64>,
+    io_psi: Option<f64>,
+}
+impl CgroupUsage {
+    fn memory_ratio(&self) -> Option<f64> {
+        match (self.memory, self.memory_max) {
+            (Some(current), Some(max)) if max > 0 => Some(current as f64 / max as f64),
+            _ => None,
+        }
+    }
+
+    fn row(&self) -> Vec<String> {
+        let opt = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+        let limit = |v: Option<String>| v.unwrap_or_else(|| "max".to_string());
+        vec![
+            self.name.clone(),
+            opt(self.cpu.map(conv_p)),
+            opt(self.memory.map(conv_b)),
+            limit(self.memory_max.map(conv_b)),
+            conv_fbs(self.read_speed),
+            conv_fbs(self.write_speed),
+            format!(
+                "{}/{}",
+                opt(self.pids.map(|p| p.to_string())),
+                limit(self.pids_max.map(|p| p.to_string()))
+            ),
+            opt(self.cpu_psi.map(conv_p)),
+            opt(self.memory_psi.map(conv_p)),
+            opt(self.io_psi.map(conv_p)),
+        ]
+    }
+}
+
+pub struct CgroupMonitor {
+    root: PathBuf,
+    groups: Vec<CgroupUsage>,
+    counters: HashMap<String, CgroupCounters>,
+    m: Screen,
+}
+
+impl StatefulWidget for CgroupMonitor {
+    fn update(&mut self) -> Result<()> {
+        let tree = Cgroup::read(&self.root, "/")?;
+        let time_delta = self.m.elapsed_since_last();
+        let mut counters = HashMap::new();
+
+        self.groups = tree
+            .flatten()
+            .into_iter()
+            .map(|(depth, cg)| {
+                let (rbytes, wbytes) = cg.io_bytes();
+                let current = CgroupCounters {
+                    cpu_usec: cg.cpu.as_ref().map(|c| c.usage_usec).unwrap_or_default(),
+                    rbytes,
+                    wbytes,
+                };
+                let prev = self.counters.get(&cg.path).copied().unwrap_or(current);
+                counters.insert(cg.path.clone(), current);
+
+                let rate = |after: u64, before: u64| after.saturating_sub(before) as f64 / time_delta;
+                CgroupUsage {
+                    name: format!("{}{}", "  ".repeat(depth), cg.name()),
+                    cpu: cg
+                        .cpu
+                        .as_ref()
+                        .map(|_| 100. * rate(current.cpu_usec, prev.cpu_usec) / 1_000_000.),
+                    memory: cg.memory_current,
+                    memory_max: cg.memory_max,
+                    read_speed: rate(current.rbytes, prev.rbytes),
+                    write_speed: rate(current.wbytes, prev.wbytes),
+                    pids: cg.pids_current,
+                    pids_max: cg.pids_max,
+                    cpu_psi: cg.cpu_pressure.map(|p| p.some.avg10),
+                    memory_psi: cg.memory_pressure.map(|p| p.some.avg10),
+                    io_psi: cg.io_pressure.map(|p| p.some.avg10),
+                }
+            })
+            .collect();
+
+        self.counters = counters;
+        self.m.update_last_time();
+
+        Ok(())
+    }
+    fn render_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(100)].as_ref())
+            .split(area);
+
+        self.render_cgroups_widget(f, chunks[0]);
+    }
+}
+
+impl CgroupMonitor {
+    pub fn new(root: &Path) -> Result<CgroupMonitor> {
+        let mut monitor = CgroupMonitor {
+            root: root.to_path_buf(),
+            groups: Vec::new(),
+            counters: HashMap::new(),
+            m: Screen::default(),
+        };
+        // First update only records counters so that rates are available on next tick
+        monitor.update()?;
+        Ok(monitor)
+    }
+
+    fn render_cgroups_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let data = self.groups.iter().map(|g| {
+            let style = match g.memory_ratio() {
+                Some(ratio) if ratio >= MEMORY_WARN_RATIO => Style::default().fg(Color::Red),
+                _ => Style::default(),
+            };
+            Row::StyledData(g.row().into_iter(), style)
+        });
+
+        let table = Table::new(CGROUP_HEADERS.iter(), data)
+            .block(
+                Block::default()
+                    .title(format!("Cgroups - {}", self.root.display()))
+                    .borders(Borders::ALL),
+            )
+            .widths(&[
+                Constraint::Percentage(28),
+                Constraint::Percentage(7),
+                Constraint::Percentage(9),
+                Constraint::Percentage(9),
+                Constraint::Percentage(9),
+                Constraint::Percentage(9),
+                Constraint::Percentage(8),
+                Constraint::Percentage(6),
+                Constraint::Percentage(6),
+                Constraint::Percentage(6),
+            ])
+            .header_gap(1)
+            .column_spacing(1);
+
+        f.render_widget(table, area);
+    }
+
+    pub fn display_loop(root: &Path) -> Result<()> {
+        let mut monitor = CgroupMonitor::new(root)?;
+        single_widget_loop(&mut monitor, Config::default())
+    }
+}
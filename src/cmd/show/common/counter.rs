@@ -0,0 +1,34 @@
+use super::RxTx;
+use crate::linux::counter::{Counter, CounterDelta};
+
+impl RxTx<Counter> {
+    /// Returns rx and tx counters with `rx` and `tx` as previous samples
+    pub fn from_initial(rx: u64, tx: u64) -> Self {
+        RxTx((Counter::new(rx), Counter::new(tx)))
+    }
+
+    /// Records new samples of both counters returning their deltas
+    pub fn update(&mut self, rx: u64, tx: u64) -> RxTx<CounterDelta> {
+        RxTx((self.rx_mut().update(rx), self.tx_mut().update(tx)))
+    }
+}
+
+impl RxTx<CounterDelta> {
+    /// Returns rx and tx deltas if both of them could be determined
+    pub fn values(&self) -> Option<(u64, u64)> {
+        Some((self.rx().value()?, self.tx().value()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_need_both_deltas() {
+        let mut counters = RxTx::<Counter>::from_initial(100, 200);
+        assert_eq!(counters.update(150, 260).values(), Some((50, 60)));
+        assert_eq!(counters.update(10, 300).values(), None);
+        assert_eq!(counters.update(20, 310).values(), Some((10, 10)));
+    }
+}
@@ -70,4 +70,14 @@ pub trait Statistic {
     /// element and new first element of set
     fn pop(&mut self) -> f64;
     fn name(&self) -> &str;
+    /// Whether the device of this stat was removed. Gone stats are no longer
+    /// updated and are dropped once their data scrolls off the graph.
+    fn is_gone(&self) -> bool {
+        false
+    }
+    /// Returns time of the oldest data point. Stats that don't implement it are
+    /// expected to always have the same number of points as the rest.
+    fn oldest(&self) -> Option<f64> {
+        None
+    }
 }
@@ -1,3 +1,4 @@
+use crate::linux::loadavg::LoadAvg;
 use tui::{
     backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
@@ -22,6 +23,15 @@ pub fn spans_from(kvspans: Vec<KvSpan<'_>>) -> Spans<'_> {
     Spans::from(kvspans.concat())
 }
 
+/// Returns a single line header with load averages and task counts
+pub fn load_header<'a>(load: &LoadAvg) -> Paragraph<'a> {
+    Paragraph::new(spans_from(vec![
+        kv_span(" load: ", &load.averages_str(), Color::Yellow, true),
+        kv_span("  tasks: ", &load.tasks_str(), Color::Yellow, true),
+        kv_span("  last pid: ", &load.last_pid.to_string(), Color::Yellow, true),
+    ]))
+}
+
 pub fn popup<B: Backend>(f: &mut Frame<B>, message: Span<'_>, title: &str, border_style: Style, button: Span<'_>) {
     let area = centered_rect(60, 20, f.size());
     let layout = Layout::default()
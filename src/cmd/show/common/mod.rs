@@ -1,4 +1,5 @@
 //! Module containing common functionality used across all widgets.
+mod counter;
 mod data;
 mod display;
 mod monitor;
@@ -6,9 +7,10 @@ mod rxtx;
 mod screen;
 mod widget;
 
+pub use crate::linux::counter::{Counter, CounterDelta};
 pub use data::*;
 pub use display::*;
-pub use monitor::Monitor;
+pub use monitor::{Hotplug, Monitor, RESCAN_INTERVAL};
 pub use rxtx::RxTx;
 pub use screen::Screen;
 pub use widget::*;
@@ -1,5 +1,6 @@
 use super::{InfoGraphWidget, Screen, StatefulWidget, Statistic, Updatable};
 use anyhow::{anyhow, Result};
+use termion::event::Key;
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
@@ -11,22 +12,80 @@ pub struct Monitor<S: Statistic> {
     pub m: Screen,
 }
 
+/// Seconds between rescans of devices by monitors of hotpluggable statistics
+pub const RESCAN_INTERVAL: f64 = 2.;
+
+/// A statistic of a device that can appear or disappear while the monitor is
+/// running like a usb disk or a veth interface of a container
+pub trait Hotplug: Statistic + Sized {
+    /// Device that a statistic is created from
+    type Device;
+    /// Returns all devices present right now
+    fn scan() -> Result<Vec<Self::Device>>;
+    /// Returns name of `device` equal to the name of its statistic
+    fn device_name(device: &Self::Device) -> String;
+    /// Marks the device as removed
+    fn set_gone(&mut self);
+}
+
+impl<S: Hotplug + From<S::Device>> Monitor<S> {
+    /// Adds statistics of devices accepted by `filter` that appeared since the last
+    /// scan and marks the ones that disappeared as gone. Statistics are only created
+    /// for new accepted devices. A device that was removed and plugged in again gets
+    /// a fresh statistic. Devices that are still present but no longer accepted by
+    /// `filter` are kept.
+    pub fn rescan<F: Fn(&S::Device) -> bool>(&mut self, filter: F) -> Result<()> {
+        let present = S::scan()?
+            .into_iter()
+            .map(|device| (S::device_name(&device), device))
+            .collect::<Vec<(String, S::Device)>>();
+        for stat in self.stats.iter_mut().filter(|s| !s.is_gone()) {
+            if !present.iter().any(|(name, _)| name == stat.name()) {
+                stat.set_gone();
+            }
+        }
+        for (name, device) in present {
+            if filter(&device) && !self.stats.iter().any(|s| !s.is_gone() && s.name() == name) {
+                self.stats.push(S::from(device));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<S: Statistic> Updatable for Monitor<S> {
     fn update(&mut self) -> Result<()> {
-        for stat in &mut self.stats {
+        for stat in self.stats.iter_mut().filter(|s| !s.is_gone()) {
             stat.update(&mut self.m)
                 .map_err(|e| anyhow!("Failed to update widget statistics - `{}`", e))?;
         }
         self.m.update_last_time();
 
         // Move x axis if time reached end
-        if self.m.elapsed_since_start() > self.m.max_x() {
-            let delta = self.stats[0].pop();
+        if self.m.elapsed_since_start() > self.m.max_x() && !self.stats.is_empty() {
+            // Stats added by a rescan have less data so the axis follows
+            // the present device with the oldest data
+            let first = self
+                .stats
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| !s.is_gone())
+                .filter_map(|(i, s)| s.oldest().map(|t| (i, t)))
+                .min_by(|(_, t1), (_, t2)| t1.partial_cmp(t2).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(i, _)| i)
+                .unwrap_or(0);
+            let delta = self.stats[first].pop();
             self.m.inc_x_axis(delta);
 
-            self.stats.iter_mut().skip(1).for_each(|s| {
-                s.pop();
-            });
+            let min_x = self.m.min_x();
+            self.stats
+                .iter_mut()
+                .enumerate()
+                .filter(|(i, s)| *i != first && s.oldest().map_or(true, |t| t < min_x))
+                .for_each(|(_, s)| {
+                    s.pop();
+                });
+            self.stats.retain(|s| !s.is_gone() || s.oldest().is_some());
         }
 
         Ok(())
@@ -47,4 +106,7 @@ impl<W: Updatable + InfoGraphWidget> StatefulWidget for W {
 
         self.render_widget(f, chunks[0]);
     }
+    fn handle_key(&mut self, key: Key) {
+        Updatable::handle_key(self, key)
+    }
 }
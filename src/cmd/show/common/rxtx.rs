@@ -1,4 +1,4 @@
-use crate::util::conv_fb;
+use crate::util::{conv_fb, conv_fbs};
 use std::{fmt::Debug, ops::AddAssign};
 
 #[derive(Default, Debug)]
@@ -32,11 +32,11 @@ impl<T: Default + Debug + AddAssign> RxTx<T> {
 impl RxTx<f64> {
     /// Returns rx value in scaled bytes/s as display string.
     pub fn rx_speed_str(&self) -> String {
-        format!("{}/s", conv_fb(*self.rx()))
+        conv_fbs(*self.rx())
     }
     /// Returns tx value in scaled bytes/s as display string.
     pub fn tx_speed_str(&self) -> String {
-        format!("{}/s", conv_fb(*self.tx()))
+        conv_fbs(*self.tx())
     }
     /// Returns scaled total rx bytes as display string.
     pub fn rx_bytes_str(&self) -> String {
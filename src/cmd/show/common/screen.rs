@@ -125,7 +125,6 @@ impl Screen {
         self.x_axis[1]
     }
 
-    #[allow(dead_code)]
     /// Returns first coordinate of x
     pub fn min_x(&self) -> f64 {
         self.x_axis[0]
@@ -1,10 +1,12 @@
 use super::{
     err_popup,
     events::{Config, Event, Events},
-    get_terminal, Screen,
+    get_terminal, load_header, Screen,
 };
+use crate::linux::loadavg::LoadAvg;
 use anyhow::Result;
 use std::borrow::Cow;
+use termion::event::Key;
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
@@ -19,6 +21,9 @@ use tui::{
 pub trait StatefulWidget {
     fn update(&mut self) -> Result<()>;
     fn render_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect);
+    /// Handles a key pressed by the user. Widgets that don't react to
+    /// input can leave this empty.
+    fn handle_key(&mut self, _key: Key) {}
 }
 
 /// Trait providing more readable way of creating graph widgets
@@ -67,6 +72,7 @@ pub trait InfoGraphWidget: GraphWidget {
 
 pub trait Updatable {
     fn update(&mut self) -> Result<()>;
+    fn handle_key(&mut self, _key: Key) {}
 }
 
 /// Loop a single widget on full screen endlessly
@@ -74,11 +80,17 @@ pub fn single_widget_loop<W: StatefulWidget>(widget: &mut W, config: Config) ->
     let mut terminal = get_terminal()?;
     let events = Events::with_config(config);
     let mut err_msg: Option<String> = None;
+    let mut load = LoadAvg::read().ok();
     loop {
         terminal.draw(|f| {
             let size = f.size();
-            let layout = Layout::default().constraints([Constraint::Percentage(100)]).split(size);
-            widget.render_widget(f, layout[0]);
+            let layout = Layout::default()
+                .constraints([Constraint::Length(1), Constraint::Min(0)])
+                .split(size);
+            if let Some(load) = &load {
+                f.render_widget(load_header(load), layout[0]);
+            }
+            widget.render_widget(f, layout[1]);
 
             if let Some(err) = err_msg.clone() {
                 err_popup(f, &err, "Press `q` to quit.");
@@ -90,11 +102,13 @@ pub fn single_widget_loop<W: StatefulWidget>(widget: &mut W, config: Config) ->
                 if input == events.exit_key() {
                     break;
                 }
+                widget.handle_key(input);
             }
             Event::Tick => {
                 if let Err(e) = widget.update() {
                     err_msg = Some(e.to_string());
                 }
+                load = LoadAvg::read().ok();
             }
         }
     }
@@ -1,5 +1,8 @@
 use super::{
-    common::{single_widget_loop, DataSeries, GraphSettings, GraphWidget, InfoGraphWidget, Monitor, Screen, Statistic},
+    common::{
+        single_widget_loop, Counter, DataSeries, GraphSettings, GraphWidget, InfoGraphWidget, Monitor, Screen,
+        Statistic,
+    },
     events::Config,
 };
 use crate::util::{conv_p, conv_t, random_color};
@@ -22,8 +25,8 @@ const TICK_RATE: u64 = 250;
 pub struct CoreUsageStat {
     name: String,
     data: DataSeries,
-    last_total_time: f64,
-    last_idle_time: f64,
+    total_time: Counter,
+    idle_time: Counter,
     last_usage: f64,
     core: Core,
 }
@@ -32,8 +35,8 @@ impl From<Core> for CoreUsageStat {
         Self {
             name: format!("cpu{}", core.id),
             data: DataSeries::new(random_color(Some(20))),
-            last_total_time: 0.,
-            last_idle_time: 0.,
+            total_time: Counter::default(),
+            idle_time: Counter::default(),
             last_usage: 0.,
             core,
         }
@@ -42,17 +45,16 @@ impl From<Core> for CoreUsageStat {
 impl Statistic for CoreUsageStat {
     fn update(&mut self, m: &mut Screen) -> Result<()> {
         if let Some(times) = self.core.cpu_time()? {
-            let total_time = times.total_time() as f64;
-            let idle_time = times.idle_time() as f64;
+            let total_delta = self.total_time.update(times.total_time() as u64);
+            let idle_delta = self.idle_time.update(times.idle_time() as u64);
 
-            let idle_delta = idle_time - self.last_idle_time;
-            let total_delta = total_time - self.last_total_time;
-
-            self.last_usage = 100. * (1.0 - idle_delta / total_delta);
-            self.data.add(m.elapsed_since_start(), self.last_usage);
-
-            self.last_total_time = total_time;
-            self.last_idle_time = idle_time;
+            // Idle time includes iowait which the kernel doesn't guarantee to be monotonic
+            if let (Some(total_delta), Some(idle_delta)) = (total_delta.value(), idle_delta.value()) {
+                if total_delta > 0 {
+                    self.last_usage = 100. * (1.0 - idle_delta as f64 / total_delta as f64);
+                }
+                self.data.add(m.elapsed_since_start(), self.last_usage);
+            }
         }
 
         Ok(())
@@ -67,6 +69,9 @@ impl Statistic for CoreUsageStat {
     fn name(&self) -> &str {
         &self.name
     }
+    fn oldest(&self) -> Option<f64> {
+        self.data.first().map(|(time, _)| time)
+    }
 }
 
 impl Monitor<CoreUsageStat> {
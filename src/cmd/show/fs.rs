@@ -0,0 +1,172 @@
+use super::{
+    common::{single_widget_loop, Screen, StatefulWidget},
+    events::Config,
+};
+use crate::{
+    linux::fs::{FsUsage, FsUsages},
+    util::{conv_b, conv_fbs, conv_p},
+};
+use anyhow::Result;
+use rsys::linux::misc::mounts;
+use std::collections::HashMap;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Row, Table},
+    Frame,
+};
+
+const FS_HEADERS: &[&str] = &[
+    "mountpoint",
+    "type",
+    "size",
+    "used",
+    "avail",
+    "usage",
+    "inodes",
+    "fill rate",
+    "full in",
+];
+const TICK_RATE: u64 = 2000;
+const USAGE_BAR_WIDTH: usize = 20;
+const USAGE_WARN: f64 = 75.;
+const USAGE_CRIT: f64 = 90.;
+
+// Formats a usage percentage as a text gauge like `[#####.....]  50.0%`
+fn usage_bar(percent: f64) -> String {
+    let filled = ((percent / 100.) * USAGE_BAR_WIDTH as f64).round() as usize;
+    let filled = filled.min(USAGE_BAR_WIDTH);
+    format!(
+        "[{}{}] {:>6}",
+        "#".repeat(filled),
+        ".".repeat(USAGE_BAR_WIDTH - filled),
+        conv_p(percent)
+    )
+}
+
+// Formats estimated seconds until full like `~3h`
+fn fmt_eta(secs: f64) -> String {
+    let secs = secs as u64;
+    match secs {
+        s if s < 60 => format!("~{}s", s),
+        s if s < 3600 => format!("~{}m", s / 60),
+        s if s < 86400 => format!("~{}h", s / 3600),
+        s => format!("~{}d", s / 86400),
+    }
+}
+
+pub struct FsMonitor {
+    filesystems: Vec<FsUsage>,
+    /// Used bytes of each mountpoint when it was first seen in this session
+    initial: HashMap<String, (u64, f64)>,
+    include_pseudo: bool,
+    m: Screen,
+}
+
+impl StatefulWidget for FsMonitor {
+    fn update(&mut self) -> Result<()> {
+        let elapsed = self.m.elapsed_since_start();
+        self.filesystems = FsUsages::from_mounts(&mounts()?, self.include_pseudo).0;
+        for fs in &self.filesystems {
+            self.initial.entry(fs.mountpoint.clone()).or_insert((fs.used, elapsed));
+        }
+        self.m.update_last_time();
+
+        Ok(())
+    }
+    fn render_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(100)].as_ref())
+            .split(area);
+
+        self.render_fs_widget(f, chunks[0]);
+    }
+}
+
+impl FsMonitor {
+    pub fn new(include_pseudo: bool) -> Result<FsMonitor> {
+        let mut monitor = FsMonitor {
+            filesystems: Vec::new(),
+            initial: HashMap::new(),
+            include_pseudo,
+            m: Screen::default(),
+        };
+        monitor.update()?;
+        Ok(monitor)
+    }
+
+    /// Returns average change of used space in bytes per second since the
+    /// filesystem was first seen in this session
+    fn fill_rate(&self, fs: &FsUsage) -> Option<f64> {
+        let (used, since) = self.initial.get(&fs.mountpoint)?;
+        let elapsed = self.m.elapsed_since_start() - since;
+        if elapsed <= 0. {
+            return None;
+        }
+        Some((fs.used as f64 - *used as f64) / elapsed)
+    }
+
+    fn render_fs_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let data = self.filesystems.iter().map(|fs| {
+            let used = fs.used_percent();
+            let style = if used >= USAGE_CRIT {
+                Style::default().fg(Color::Red)
+            } else if used >= USAGE_WARN {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            let rate = self.fill_rate(fs);
+            let eta = rate
+                .filter(|r| *r > 0.)
+                .map(|r| fmt_eta(fs.available as f64 / r))
+                .unwrap_or_else(|| "-".to_string());
+            let rate = match rate {
+                Some(r) if r < 0. => format!("-{}", conv_fbs(-r)),
+                Some(r) => conv_fbs(r),
+                None => "-".to_string(),
+            };
+
+            Row::StyledData(
+                vec![
+                    fs.mountpoint.clone(),
+                    fs.fstype.clone(),
+                    conv_b(fs.size),
+                    conv_b(fs.used),
+                    conv_b(fs.available),
+                    usage_bar(used),
+                    fs.inodes_used_percent().map(conv_p).unwrap_or_else(|| "-".to_string()),
+                    rate,
+                    eta,
+                ]
+                .into_iter(),
+                style,
+            )
+        });
+
+        let table = Table::new(FS_HEADERS.iter(), data)
+            .block(Block::default().title("Filesystems").borders(Borders::ALL))
+            .widths(&[
+                Constraint::Percentage(20),
+                Constraint::Percentage(7),
+                Constraint::Percentage(8),
+                Constraint::Percentage(8),
+                Constraint::Percentage(8),
+                Constraint::Length(USAGE_BAR_WIDTH as u16 + 9),
+                Constraint::Percentage(7),
+                Constraint::Percentage(10),
+                Constraint::Percentage(7),
+            ])
+            .header_gap(1)
+            .column_spacing(1);
+
+        f.render_widget(table, area);
+    }
+
+    pub fn display_loop(include_pseudo: bool) -> Result<()> {
+        let mut monitor = FsMonitor::new(include_pseudo)?;
+        single_widget_loop(&mut monitor, Config::new(TICK_RATE))
+    }
+}
@@ -1,20 +1,40 @@
+mod battery;
+mod cgroup;
 mod common;
 mod cpu;
 mod events;
+mod fs;
 mod net;
+mod netstat;
+mod power;
+mod pressure;
 mod ps;
+mod sensors;
+mod sockets;
 mod storage;
+mod wireless;
 
-use crate::RsysCli;
-use common::{err_popup, Monitor, StatefulWidget};
+use crate::{cmd::common::IfaceFilter, linux::loadavg::LoadAvg, RsysCli};
+use battery::BatteryMonitor;
+use cgroup::CgroupMonitor;
+use common::{err_popup, load_header, Monitor, StatefulWidget};
 use cpu::{CoreFrequencyStat, CoreUsageStat};
 use events::{Config, Event, Events};
-use net::IfaceSpeedStat;
+use fs::FsMonitor;
+use net::{NetMonitor, NetView};
+use netstat::NetStatStat;
+use power::RaplStat;
+use pressure::PressureMonitor;
 use ps::ProcessMonitor;
-use storage::StorageSpeedStat;
+use sensors::SensorStat;
+use sockets::SocketStat;
+use storage::{StorageMonitor, StorageView};
 
 use anyhow::Error;
-use std::io::{self, stdout};
+use std::{
+    io::{self, stdout},
+    path::PathBuf,
+};
 use structopt::StructOpt;
 use termion::{
     event::Key,
@@ -41,36 +61,94 @@ pub fn get_terminal() -> Result<Term> {
 
 #[derive(StructOpt, Clone)]
 pub enum ShowCmd {
-    /// Draw interface rx/tx speed
-    Interface { name: String },
+    /// Draw interface rx/tx speed, packet, error or drop rates. Press `v` to switch between views.
+    /// Signal level of wireless interfaces is graphed below.
+    Interface {
+        /// Name of the interface or a glob pattern like `eth*` matching several of them
+        name: String,
+        #[structopt(long, default_value = "bytes", possible_values = NetView::VARIANTS)]
+        /// Metric graphed initially
+        view: NetView,
+    },
     /// Draw cpu usage
     CpuUsage,
     /// Draw cpu core frequencies
     CpuFreq,
-    /// Display I/O stats for storage devices
-    Storage,
-    /// Display network interfaces graphs
-    Net,
+    /// Display I/O stats for storage devices. Press `v` to switch between views.
+    Storage {
+        #[structopt(long, default_value = "throughput", possible_values = StorageView::VARIANTS)]
+        /// Metric graphed initially
+        view: StorageView,
+    },
+    /// Display network interfaces graphs. Press `v` to switch between speed, packet, error and drop rates.
+    Net {
+        #[structopt(long, default_value = "bytes", possible_values = NetView::VARIANTS)]
+        /// Metric graphed initially
+        view: NetView,
+        #[structopt(flatten)]
+        filter: IfaceFilter,
+    },
+    /// Draw rates of TCP retransmits and resets, listen queue overflows, UDP errors and IP fragmentation
+    /// with usage of the connection tracking table
+    Netstat,
     /// Display process list
     Ps,
+    /// Display cgroup v2 hierarchy with usage and limits of each group
+    Cgroups {
+        #[structopt(long, default_value = "/sys/fs/cgroup", parse(from_os_str))]
+        /// Mountpoint of the cgroup v2 hierarchy
+        root: PathBuf,
+    },
+    /// Draw pressure stall information of cpu, memory and io
+    Pressure,
+    /// Draw temperatures and display readings of hardware sensors
+    Sensors {
+        #[structopt(long, default_value = "/sys", parse(from_os_str))]
+        /// Mountpoint of sysfs
+        sysfs: PathBuf,
+    },
+    /// Draw power consumption of cpu packages, cores and dram from RAPL counters
+    Power {
+        #[structopt(long, default_value = "/sys", parse(from_os_str))]
+        /// Mountpoint of sysfs
+        sysfs: PathBuf,
+    },
+    /// Draw battery capacity next to cpu usage and display power supply details
+    Battery {
+        #[structopt(long, default_value = "/sys", parse(from_os_str))]
+        /// Mountpoint of sysfs
+        sysfs: PathBuf,
+    },
+    /// Draw number of TCP connections in each state
+    Sockets,
+    /// Display usage of mounted filesystems sorted by fullness
+    Fs {
+        #[structopt(short, long)]
+        /// Include pseudo filesystems like proc or sysfs
+        all: bool,
+    },
     /// Display all graphs at once
     All,
 }
 
 impl RsysCli {
-    pub fn show(&self, cmd: ShowCmd) {
-        let result = match cmd {
-            ShowCmd::Interface { name } => Monitor::<IfaceSpeedStat>::single_iface_loop(&name),
+    pub fn show(&self, cmd: ShowCmd) -> Result<()> {
+        match cmd {
+            ShowCmd::Interface { name, view } => NetMonitor::single_iface_loop(&name, view),
             ShowCmd::CpuFreq => Monitor::<CoreFrequencyStat>::graph_loop(),
             ShowCmd::CpuUsage => Monitor::<CoreUsageStat>::graph_loop(),
-            ShowCmd::Storage => Monitor::<StorageSpeedStat>::graph_loop(),
-            ShowCmd::Net => Monitor::<IfaceSpeedStat>::graph_loop(None),
+            ShowCmd::Storage { view } => StorageMonitor::graph_loop(view),
+            ShowCmd::Net { view, filter } => NetMonitor::graph_loop(filter, view),
+            ShowCmd::Netstat => Monitor::<NetStatStat>::graph_loop(),
             ShowCmd::Ps => ProcessMonitor::display_loop(),
+            ShowCmd::Cgroups { root } => CgroupMonitor::display_loop(&root),
+            ShowCmd::Pressure => PressureMonitor::graph_loop(),
+            ShowCmd::Sensors { sysfs } => Monitor::<SensorStat>::graph_loop(&sysfs),
+            ShowCmd::Battery { sysfs } => BatteryMonitor::graph_loop(&sysfs),
+            ShowCmd::Power { sysfs } => Monitor::<RaplStat>::graph_loop(&sysfs),
+            ShowCmd::Fs { all } => FsMonitor::display_loop(all),
+            ShowCmd::Sockets => Monitor::<SocketStat>::graph_loop(),
             ShowCmd::All => show_all_loop(),
-        };
-
-        if let Err(e) = result {
-            eprintln!("Error: {}", e);
         }
     }
 }
@@ -80,21 +158,28 @@ pub fn show_all_loop() -> Result<()> {
     let mut terminal = get_terminal()?;
     let events = Events::with_config(Config::new(200));
     let mut cpumon = Monitor::<CoreFrequencyStat>::new()?;
-    let mut ifacemon = Monitor::<IfaceSpeedStat>::new(None)?;
-    let mut stormon = Monitor::<StorageSpeedStat>::new()?;
+    let mut ifacemon = NetMonitor::new(IfaceFilter::default(), NetView::Bytes)?;
+    let mut stormon = StorageMonitor::new(StorageView::Throughput)?;
     let mut errors: Vec<Error> = Vec::new();
     let mut show_errors = true;
     let mut was_error = false;
+    let mut load = LoadAvg::read().ok();
     loop {
         terminal.draw(|f| {
             let size = f.size();
+            let layout = Layout::default()
+                .constraints([Constraint::Length(1), Constraint::Min(0)])
+                .split(size);
+            if let Some(load) = &load {
+                f.render_widget(load_header(load), layout[0]);
+            }
             let layout = Layout::default()
                 .constraints([
                     Constraint::Percentage(33),
                     Constraint::Percentage(33),
                     Constraint::Percentage(33),
                 ])
-                .split(size);
+                .split(layout[1]);
 
             if !errors.is_empty() && show_errors {
                 let error = &errors[0];
@@ -132,6 +217,7 @@ pub fn show_all_loop() -> Result<()> {
                 if let Err(e) = stormon.update() {
                     errors.push(e);
                 }
+                load = LoadAvg::read().ok();
             }
         }
     }
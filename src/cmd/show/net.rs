@@ -1,13 +1,23 @@
 use super::{
     common::{
-        kv_span, single_widget_loop, spans_from, DataSeries, GraphSettings, GraphWidget, InfoGraphWidget, Monitor,
-        RxTx, Screen, Statistic,
+        kv_span, single_widget_loop, spans_from, Counter, DataSeries, GraphSettings, GraphWidget, Hotplug,
+        InfoGraphWidget, Monitor, RxTx, Screen, Statistic, Updatable, RESCAN_INTERVAL,
     },
     events::Config,
+    wireless::WirelessMonitor,
 };
-use crate::util::{conv_fbs, conv_t, random_color};
-use anyhow::{anyhow, Result};
+use crate::{
+    cmd::common::IfaceFilter,
+    linux::{
+        link::{LinkDetails, CLASS_NET_DIR},
+        SYSFS,
+    },
+    util::{conv_net_fbs, conv_t, random_color},
+};
+use anyhow::{anyhow, Error, Result};
 use rsys::linux::net::{ifaces, Interface};
+use std::{path::Path, str::FromStr};
+use termion::event::Key;
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
@@ -21,39 +31,156 @@ use tui::{
 const X_AXIS: (f64, f64) = (0., 30.0);
 const Y_AXIS: (f64, f64) = (0., 100.0);
 const TICK_RATE: u64 = 300;
+const VIEW_KEY: Key = Key::Char('v');
+// Seconds for which a link flap stays highlighted
+const FLAP_HIGHLIGHT: f64 = 10.;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Metric graphed by the network dashboard
+pub enum NetView {
+    /// Bytes received and transmitted per second
+    Bytes,
+    /// Packets received and transmitted per second
+    Packets,
+    /// Receive and transmit errors per second
+    Errors,
+    /// Dropped packets per second
+    Drops,
+}
+impl NetView {
+    pub const VARIANTS: &'static [&'static str] = &["bytes", "packets", "errors", "drops"];
+
+    /// Returns the view displayed after this one when switching views
+    fn next(self) -> NetView {
+        match self {
+            NetView::Bytes => NetView::Packets,
+            NetView::Packets => NetView::Errors,
+            NetView::Errors => NetView::Drops,
+            NetView::Drops => NetView::Bytes,
+        }
+    }
+
+    fn y_title(self) -> &'static str {
+        match self {
+            NetView::Bytes => "speed",
+            NetView::Packets => "packets",
+            NetView::Errors => "errors",
+            NetView::Drops => "drops",
+        }
+    }
+
+    fn y_label(self, value: f64) -> String {
+        match self {
+            NetView::Bytes => conv_net_fbs(value),
+            _ => format!("{:.1}/s", value),
+        }
+    }
+
+    // Lowest upper bound of y axis so that an idle interface doesn't produce a flat zero range
+    fn min_y_max(self) -> f64 {
+        match self {
+            NetView::Bytes => 100.,
+            _ => 1.,
+        }
+    }
+}
+impl FromStr for NetView {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "bytes" => Ok(NetView::Bytes),
+            "packets" => Ok(NetView::Packets),
+            "errors" => Ok(NetView::Errors),
+            "drops" => Ok(NetView::Drops),
+            _ => Err(anyhow!("Unknown network view `{}`", s)),
+        }
+    }
+}
 
 pub struct IfaceSpeedStat {
     iface: Interface,
     data: RxTx<DataSeries>,
-    prev_bytes: RxTx<u64>,
+    packets_data: RxTx<DataSeries>,
+    errors_data: RxTx<DataSeries>,
+    drops_data: RxTx<DataSeries>,
+    bytes: RxTx<Counter>,
+    packets: RxTx<Counter>,
+    errors: RxTx<Counter>,
+    drops: RxTx<Counter>,
     curr_speed: RxTx<f64>,
     total: RxTx<f64>,
+    packet_rate: RxTx<f64>,
+    error_rate: RxTx<f64>,
+    drop_rate: RxTx<f64>,
+    link: Option<LinkDetails>,
+    // Carrier changes when monitoring started, later changes are link flaps
+    initial_carrier_changes: Option<u64>,
+    // Time of the last observed link flap
+    last_flap: Option<f64>,
+    gone: bool,
 }
 impl Statistic for IfaceSpeedStat {
-    // Updates core and returns its new frequency
     fn update(&mut self, m: &mut Screen) -> Result<()> {
-        self.iface
-            .update()
-            .map_err(|e| anyhow!("Failed to update interface `{}` - {}", self.iface.name, e.to_string()))?;
-
-        let (delta_rx, delta_tx) = self.delta();
+        if let Err(e) = self.iface.update() {
+            // Interface was deleted in the meantime
+            if !Path::new(SYSFS).join(CLASS_NET_DIR).join(&self.iface.name).exists() {
+                self.gone = true;
+                return Ok(());
+            }
+            return Err(anyhow!(
+                "Failed to update interface `{}` - {}",
+                self.iface.name,
+                e.to_string()
+            ));
+        }
 
-        self.total.inc(delta_rx, delta_tx);
+        if let Ok(link) = LinkDetails::read(SYSFS, &self.iface.name) {
+            let previous = self.link.as_ref().and_then(|l| l.carrier_changes);
+            if previous.is_some() && previous != link.carrier_changes {
+                self.last_flap = Some(m.elapsed_since_start());
+            }
+            self.link = Some(link);
+        }
 
-        self.curr_speed = RxTx((delta_rx / m.elapsed_since_last(), delta_tx / m.elapsed_since_last()));
-        self.data.rx_mut().add(m.elapsed_since_start(), *self.curr_speed.rx());
-        self.data.tx_mut().add(m.elapsed_since_start(), *self.curr_speed.tx());
+        let stat = &self.iface.stat;
+        let bytes = self.bytes.update(stat.rx_bytes, stat.tx_bytes).values();
+        let packets = self.packets.update(stat.rx_packets, stat.tx_packets).values();
+        let errors = self.errors.update(stat.rx_errs, stat.tx_errs).values();
+        let drops = self.drops.update(stat.rx_drop, stat.tx_drop).values();
+        // Counters of a recreated interface start from zero so there is no valid delta
+        let (bytes, packets, errors, drops) = match (bytes, packets, errors, drops) {
+            (Some(bytes), Some(packets), Some(errors), Some(drops)) => (bytes, packets, errors, drops),
+            _ => return Ok(()),
+        };
 
-        m.set_if_y_max(*self.curr_speed.rx() + 100.);
-        m.set_if_y_max(*self.curr_speed.tx() + 100.);
+        let elapsed = m.elapsed_since_last();
+        let rate = |(rx, tx): (u64, u64)| RxTx((rx as f64 / elapsed, tx as f64 / elapsed));
+        self.total.inc(bytes.0 as f64, bytes.1 as f64);
+        self.curr_speed = rate(bytes);
+        self.packet_rate = rate(packets);
+        self.error_rate = rate(errors);
+        self.drop_rate = rate(drops);
 
-        self.prev_bytes = RxTx((self.iface.stat.rx_bytes, self.iface.stat.tx_bytes));
+        let time = m.elapsed_since_start();
+        for (data, rate) in [
+            (&mut self.data, &self.curr_speed),
+            (&mut self.packets_data, &self.packet_rate),
+            (&mut self.errors_data, &self.error_rate),
+            (&mut self.drops_data, &self.drop_rate),
+        ] {
+            data.rx_mut().add(time, *rate.rx());
+            data.tx_mut().add(time, *rate.tx());
+        }
 
         Ok(())
     }
     fn pop(&mut self) -> f64 {
         let removed = self.data.rx_mut().pop();
         self.data.tx_mut().pop();
+        for data in [&mut self.packets_data, &mut self.errors_data, &mut self.drops_data] {
+            data.rx_mut().pop();
+            data.tx_mut().pop();
+        }
 
         if let Some(point) = self.data.rx().first() {
             return point.0 - removed.0;
@@ -63,37 +190,132 @@ impl Statistic for IfaceSpeedStat {
     fn name(&self) -> &str {
         &self.iface.name
     }
+    fn is_gone(&self) -> bool {
+        self.gone
+    }
+    fn oldest(&self) -> Option<f64> {
+        self.data.rx().first().map(|(time, _)| time)
+    }
+}
+impl Hotplug for IfaceSpeedStat {
+    type Device = Interface;
+
+    fn scan() -> Result<Vec<Interface>> {
+        Ok(ifaces()?.0)
+    }
+    fn device_name(iface: &Interface) -> String {
+        iface.name.clone()
+    }
+    fn set_gone(&mut self) {
+        self.gone = true;
+    }
 }
 impl IfaceSpeedStat {
     fn new(iface: Interface) -> Self {
-        let rx = iface.stat.rx_bytes;
-        let tx = iface.stat.tx_bytes;
+        let (rx_color, tx_color) = (random_color(Some(20)), random_color(Some(20)));
+        let series = || RxTx((DataSeries::new(rx_color), DataSeries::new(tx_color)));
+        let stat = &iface.stat;
+        let link = LinkDetails::read(SYSFS, &iface.name).ok();
         Self {
-            iface,
-            data: RxTx((
-                DataSeries::new(random_color(Some(20))),
-                DataSeries::new(random_color(Some(20))),
-            )),
-            prev_bytes: RxTx((rx, tx)),
+            data: series(),
+            packets_data: series(),
+            errors_data: series(),
+            drops_data: series(),
+            bytes: RxTx::<Counter>::from_initial(stat.rx_bytes, stat.tx_bytes),
+            packets: RxTx::<Counter>::from_initial(stat.rx_packets, stat.tx_packets),
+            errors: RxTx::<Counter>::from_initial(stat.rx_errs, stat.tx_errs),
+            drops: RxTx::<Counter>::from_initial(stat.rx_drop, stat.tx_drop),
             curr_speed: RxTx::default(),
             total: RxTx::default(),
+            packet_rate: RxTx::default(),
+            error_rate: RxTx::default(),
+            drop_rate: RxTx::default(),
+            initial_carrier_changes: link.as_ref().and_then(|l| l.carrier_changes),
+            link,
+            last_flap: None,
+            gone: false,
+            iface,
+        }
+    }
+    // Returns received and transmitted series of view
+    fn series(&self, view: NetView) -> &RxTx<DataSeries> {
+        match view {
+            NetView::Bytes => &self.data,
+            NetView::Packets => &self.packets_data,
+            NetView::Errors => &self.errors_data,
+            NetView::Drops => &self.drops_data,
         }
     }
-    fn delta(&mut self) -> (f64, f64) {
-        (
-            (self.iface.stat.rx_bytes - self.prev_bytes.rx()) as f64,
-            (self.iface.stat.tx_bytes - self.prev_bytes.tx()) as f64,
-        )
+    // Formats rx and tx rate as `rx / tx` in red if any of them is non zero
+    fn problem_span<'a>(key: &'a str, rate: &RxTx<f64>) -> Spans<'a> {
+        let color = if *rate.rx() > 0. || *rate.tx() > 0. {
+            Color::Red
+        } else {
+            Color::White
+        };
+        spans_from(vec![kv_span(
+            key.to_string(),
+            format!("{:.1} / {:.1} /s", rate.rx(), rate.tx()),
+            color,
+            true,
+        )])
+    }
+    // Number of carrier changes since monitoring started
+    fn flaps(&self) -> Option<u64> {
+        let current = self.link.as_ref()?.carrier_changes?;
+        Some(current.saturating_sub(self.initial_carrier_changes.unwrap_or(current)))
+    }
+    // Link state, driver and flaps of the interface. Lines turn red when carrier is lost
+    // and while a flap is recent
+    fn link_spans(&self, time: f64) -> Vec<Spans> {
+        let link = match &self.link {
+            Some(link) => link,
+            None => return vec![],
+        };
+        let link_color = if link.carrier == Some(false) {
+            Color::Red
+        } else {
+            Color::White
+        };
+        let flaps = self.flaps().unwrap_or_default();
+        let flaps_color = match self.last_flap {
+            Some(last) if time - last < FLAP_HIGHLIGHT => Color::Red,
+            _ if flaps > 0 => Color::Yellow,
+            _ => Color::White,
+        };
+        let driver = match &link.driver {
+            Some(driver) => format!("{} ({})", driver, link.kind),
+            None => link.kind.to_string(),
+        };
+        vec![
+            spans_from(vec![kv_span(" link: ".to_string(), link.link_str(), link_color, true)]),
+            spans_from(vec![kv_span(" drv : ".to_string(), driver, Color::White, true)]),
+            spans_from(vec![kv_span(
+                " flap: ".to_string(),
+                flaps.to_string(),
+                flaps_color,
+                true,
+            )]),
+        ]
+    }
+    // Name of the interface as displayed in legend and info
+    fn label(&self) -> String {
+        if self.gone {
+            format!("{} (gone)", self.iface.name)
+        } else {
+            self.iface.name.clone()
+        }
     }
-    fn info(&self) -> Paragraph {
-        Paragraph::new(vec![
+    fn info(&self, time: f64) -> Paragraph {
+        let color = if self.gone { Color::DarkGray } else { Color::Green };
+        let mut spans = vec![
             Spans::from(Span::styled(
-                &self.iface.name,
-                Style::default().add_modifier(Modifier::BOLD).fg(Color::Green),
+                self.label(),
+                Style::default().add_modifier(Modifier::BOLD).fg(color),
             )),
             spans_from(vec![kv_span(
                 " Vrx : ",
-                &self.curr_speed.rx_speed_str(),
+                &conv_net_fbs(*self.curr_speed.rx()),
                 self.data.rx().color,
                 true,
             )]),
@@ -105,7 +327,7 @@ impl IfaceSpeedStat {
             )]),
             spans_from(vec![kv_span(
                 " Vtx : ",
-                &self.curr_speed.tx_speed_str(),
+                &conv_net_fbs(*self.curr_speed.tx()),
                 self.data.tx().color,
                 true,
             )]),
@@ -115,6 +337,14 @@ impl IfaceSpeedStat {
                 self.data.tx().color,
                 true,
             )]),
+            spans_from(vec![kv_span(
+                " pps : ".to_string(),
+                format!("{:.1} / {:.1}", self.packet_rate.rx(), self.packet_rate.tx()),
+                Color::White,
+                true,
+            )]),
+            IfaceSpeedStat::problem_span(" errs: ", &self.error_rate),
+            IfaceSpeedStat::problem_span(" drop: ", &self.drop_rate),
             spans_from(vec![kv_span(" ipv4: ", &self.iface.ipv4, Color::White, true)]),
             spans_from(vec![kv_span(" ipv6: ", &self.iface.ipv6, Color::White, true)]),
             spans_from(vec![kv_span(
@@ -124,7 +354,9 @@ impl IfaceSpeedStat {
                 true,
             )]),
             spans_from(vec![kv_span(" mac : ", &self.iface.mac_address, Color::White, true)]),
-        ])
+        ];
+        spans.extend(self.link_spans(time));
+        Paragraph::new(spans)
     }
 }
 impl From<Interface> for IfaceSpeedStat {
@@ -133,90 +365,142 @@ impl From<Interface> for IfaceSpeedStat {
     }
 }
 
-impl GraphWidget for Monitor<IfaceSpeedStat> {
+/// Network interfaces graph that picks up interfaces created or deleted while running
+pub struct NetMonitor {
+    monitor: Monitor<IfaceSpeedStat>,
+    view: NetView,
+    filter: IfaceFilter,
+    last_scan: f64,
+}
+
+impl Updatable for NetMonitor {
+    fn update(&mut self) -> Result<()> {
+        Updatable::update(&mut self.monitor)?;
+
+        let elapsed = self.monitor.m.elapsed_since_start();
+        if elapsed - self.last_scan >= RESCAN_INTERVAL {
+            let filter = &self.filter;
+            // A failed scan is retried after next interval, present interfaces are still updated
+            let _ = self.monitor.rescan(|iface| filter.accepts(iface, SYSFS));
+            self.last_scan = elapsed;
+        }
+        self.fit_y_axis();
+
+        Ok(())
+    }
+    fn handle_key(&mut self, key: Key) {
+        if key == VIEW_KEY {
+            self.view = self.view.next();
+            self.fit_y_axis();
+        }
+    }
+}
+
+impl GraphWidget for NetMonitor {
     fn datasets(&self) -> Vec<Dataset> {
         let mut data = Vec::new();
-        for iface in &self.stats {
+        for iface in &self.monitor.stats {
+            let series = iface.series(self.view);
             data.push(
                 Dataset::default()
-                    .name(format!("{} rx", iface.iface.name))
+                    .name(format!("{} rx", iface.label()))
                     .marker(symbols::Marker::Dot)
-                    .style(Style::default().fg(iface.data.rx().color))
-                    .data(&iface.data.rx().dataset()),
+                    .style(Style::default().fg(series.rx().color))
+                    .data(series.rx().dataset()),
             );
             data.push(
                 Dataset::default()
-                    .name(format!("{} tx", iface.iface.name))
+                    .name(format!("{} tx", iface.label()))
                     .marker(symbols::Marker::Braille)
-                    .style(Style::default().fg(iface.data.tx().color))
-                    .data(&iface.data.tx().dataset()),
+                    .style(Style::default().fg(series.tx().color))
+                    .data(series.tx().dataset()),
             );
         }
         data
     }
     fn settings(&self) -> GraphSettings {
+        let view = self.view;
         GraphSettings::new()
             .title(
-                "Network Speed",
+                format!("Network {} (`v` - next view)", view.y_title()),
                 Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan),
             )
             .x_title("Time", Style::default().fg(Color::White))
-            .y_title("Speed", Style::default().fg(Color::White))
-            .x_labels(self.m.x_bounds_labels(conv_t, 4))
-            .y_labels(self.m.y_bounds_labels(conv_fbs, 5))
+            .y_title(view.y_title(), Style::default().fg(Color::White))
+            .x_labels(self.monitor.m.x_bounds_labels(conv_t, 4))
+            .y_labels(self.monitor.m.y_bounds_labels(|v| view.y_label(v), 5))
     }
     fn monitor(&self) -> &Screen {
-        &self.m
+        &self.monitor.m
     }
 }
 
-impl Monitor<IfaceSpeedStat> {
-    pub fn new(filter: Option<&[&str]>) -> Result<Monitor<IfaceSpeedStat>> {
+impl NetMonitor {
+    pub fn new(filter: IfaceFilter, view: NetView) -> Result<NetMonitor> {
         let stats = ifaces()?
             .0
             .into_iter()
-            .filter(|s| {
-                if let Some(filters) = filter {
-                    for f in filters {
-                        if *f == s.name {
-                            return true;
-                        } else {
-                            continue;
-                        }
-                    }
-                    false
-                } else {
-                    true
-                }
-            })
+            .filter(|iface| filter.accepts(iface, SYSFS))
             .map(IfaceSpeedStat::from)
             .collect::<Vec<IfaceSpeedStat>>();
 
         if stats.is_empty() {
-            return Err(anyhow!("No interface matched passed in filter `{:?}`", filter));
+            return Err(anyhow!(
+                "No interface matched included `{:?}` and excluded `{:?}` patterns",
+                filter.include,
+                filter.exclude
+            ));
         }
 
-        Ok(Monitor {
-            stats,
-            m: Screen::new(X_AXIS, Y_AXIS),
+        Ok(NetMonitor {
+            monitor: Monitor {
+                stats,
+                m: Screen::new(X_AXIS, Y_AXIS),
+            },
+            view,
+            filter,
+            last_scan: 0.,
         })
     }
 
-    pub fn graph_loop(filter: Option<&[&str]>) -> Result<()> {
-        let mut monitor = Self::new(filter)?;
+    // Scales y axis to the highest value of the current view that is still on screen
+    fn fit_y_axis(&mut self) {
+        let view = self.view;
+        let max = self
+            .monitor
+            .stats
+            .iter()
+            .flat_map(|s| {
+                let series = s.series(view);
+                series
+                    .rx()
+                    .dataset()
+                    .iter()
+                    .chain(series.tx().dataset())
+                    .map(|(_, y)| *y)
+            })
+            .fold(0., f64::max);
+        self.monitor.m.set_y_max((max * 1.1).max(view.min_y_max()));
+    }
+
+    pub fn graph_loop(filter: IfaceFilter, view: NetView) -> Result<()> {
+        let mut monitor = Self::new(filter, view)?;
         single_widget_loop(&mut monitor, Config::new(TICK_RATE))
     }
 
-    pub fn single_iface_loop(name: &str) -> Result<()> {
-        Self::graph_loop(Some(&[name]))
+    pub fn single_iface_loop(name: &str, view: NetView) -> Result<()> {
+        if WirelessMonitor::is_wireless(name) {
+            return WirelessMonitor::graph_loop(name, view);
+        }
+        Self::graph_loop(IfaceFilter::with_pattern(name), view)
     }
 }
-impl InfoGraphWidget for Monitor<IfaceSpeedStat> {
+impl InfoGraphWidget for NetMonitor {
     const DIRECTION: Direction = Direction::Horizontal;
     const CONSTRAINTS: [Constraint; 2] = [Constraint::Percentage(20), Constraint::Min(80)];
 
     fn render_extra_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
-        let count = self.stats.len();
+        let count = self.monitor.stats.len();
         let percentage = if count == 0 { 1_u16 } else { (100 / count) as u16 };
         let constraints = (0..count)
             .into_iter()
@@ -226,9 +510,10 @@ impl InfoGraphWidget for Monitor<IfaceSpeedStat> {
             .direction(Direction::Vertical)
             .constraints(constraints)
             .split(area);
-        self.stats
+        self.monitor
+            .stats
             .iter()
             .enumerate()
-            .for_each(|(i, s)| f.render_widget(s.info(), chunks[i]));
+            .for_each(|(i, s)| f.render_widget(s.info(self.monitor.m.elapsed_since_start()), chunks[i]));
     }
 }
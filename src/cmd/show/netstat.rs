@@ -0,0 +1,225 @@
+use super::{
+    common::{
+        kv_span, single_widget_loop, spans_from, Counter, DataSeries, GraphSettings, GraphWidget, InfoGraphWidget,
+        Monitor, Screen, Statistic,
+    },
+    events::Config,
+};
+use crate::{
+    linux::{conntrack::Conntrack, netstat::NetStat},
+    util::{conv_p, conv_t, random_color},
+};
+use anyhow::{anyhow, Result};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Dataset, Gauge, Paragraph},
+    Frame,
+};
+
+const X_AXIS: (f64, f64) = (0., 30.0);
+const Y_AXIS: (f64, f64) = (0., 1.);
+const TICK_RATE: u64 = 1000;
+// Usage of the connection tracking table in percent from which its gauge turns yellow
+const CONNTRACK_NOTICE_PERCENT: f64 = 75.;
+const CONNTRACK_GAUGE_HEIGHT: u16 = 3;
+
+#[derive(Debug)]
+// Rate of a single protocol counter
+struct CounterSeries {
+    name: &'static str,
+    counter: Counter,
+    data: DataSeries,
+    rate: f64,
+    total: u64,
+}
+
+#[derive(Debug)]
+// Rates of TCP, UDP and IP error counters
+pub struct NetStatStat {
+    series: Vec<CounterSeries>,
+    out_segs: Counter,
+    retrans_segs: Counter,
+    // Percentage of retransmitted segments out of segments sent since last update
+    retrans_percent: f64,
+    // Missing when the `nf_conntrack` module is not loaded
+    conntrack: Option<Conntrack>,
+}
+impl Statistic for NetStatStat {
+    fn update(&mut self, m: &mut Screen) -> Result<()> {
+        let stat = NetStat::read().map_err(|e| anyhow!("Failed to read protocol counters - {}", e))?;
+        self.conntrack = Conntrack::read().ok();
+        let time = m.elapsed_since_start();
+        let elapsed = m.elapsed_since_last();
+
+        let out_segs = self.out_segs.update(stat.tcp_out_segs).value();
+        let retrans_segs = self.retrans_segs.update(stat.tcp_retrans_segs).value();
+        if let (Some(sent), Some(retrans)) = (out_segs, retrans_segs) {
+            self.retrans_percent = if sent == 0 {
+                0.
+            } else {
+                100. * retrans as f64 / sent as f64
+            };
+        }
+        for (series, (_, value)) in self.series.iter_mut().zip(stat.counters()) {
+            series.total = value;
+            if let Some(delta) = series.counter.update(value).value() {
+                series.rate = delta as f64 / elapsed;
+                series.data.add(time, series.rate);
+                m.set_if_y_max(series.rate * 1.1);
+            }
+        }
+
+        Ok(())
+    }
+    fn pop(&mut self) -> f64 {
+        let mut removed = (0., 0.);
+        for (i, series) in self.series.iter_mut().enumerate() {
+            let point = series.data.pop();
+            if i == 0 {
+                removed = point;
+            }
+        }
+
+        if let Some(point) = self.series.first().and_then(|s| s.data.first()) {
+            return point.0 - removed.0;
+        }
+        0.
+    }
+    fn name(&self) -> &str {
+        "netstat"
+    }
+}
+impl NetStatStat {
+    fn new(stat: NetStat) -> Self {
+        Self {
+            series: stat
+                .counters()
+                .into_iter()
+                .map(|(name, value)| CounterSeries {
+                    name,
+                    counter: Counter::new(value),
+                    data: DataSeries::new(random_color(Some(20))),
+                    rate: 0.,
+                    total: value,
+                })
+                .collect(),
+            out_segs: Counter::new(stat.tcp_out_segs),
+            retrans_segs: Counter::new(stat.tcp_retrans_segs),
+            retrans_percent: 0.,
+            conntrack: Conntrack::read().ok(),
+        }
+    }
+
+    fn info(&self) -> Paragraph {
+        let mut spans = vec![
+            Spans::from(Span::styled(
+                "Protocol counters",
+                Style::default().add_modifier(Modifier::BOLD).fg(Color::Green),
+            )),
+            spans_from(vec![kv_span(
+                " retransmitted      : ".to_string(),
+                conv_p(self.retrans_percent),
+                Color::White,
+                true,
+            )]),
+        ];
+        spans.extend(self.series.iter().map(|s| {
+            spans_from(vec![kv_span(
+                format!(" {:19}: ", s.name),
+                format!("{:.1}/s (Σ {})", s.rate, s.total),
+                s.data.color,
+                true,
+            )])
+        }));
+        Paragraph::new(spans)
+    }
+
+    fn conntrack_gauge(conntrack: &Conntrack) -> Gauge {
+        let percent = conntrack.usage_percent();
+        let (color, title) = if conntrack.is_nearly_full() {
+            (Color::Red, "Conntrack - nearly full, new connections may be dropped")
+        } else if percent >= CONNTRACK_NOTICE_PERCENT {
+            (Color::Yellow, "Conntrack - filling up")
+        } else {
+            (Color::Green, "Conntrack")
+        };
+        Gauge::default()
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .gauge_style(Style::default().fg(color))
+            .ratio((percent / 100.).clamp(0., 1.))
+            .label(format!("{}/{} ({})", conntrack.count, conntrack.max, conv_p(percent)))
+    }
+}
+
+impl GraphWidget for Monitor<NetStatStat> {
+    fn datasets(&self) -> Vec<Dataset> {
+        let mut data = Vec::new();
+        for stat in &self.stats {
+            for series in &stat.series {
+                data.push(
+                    Dataset::default()
+                        .name(series.name)
+                        .marker(symbols::Marker::Braille)
+                        .style(Style::default().fg(series.data.color))
+                        .data(&series.data.dataset()),
+                );
+            }
+        }
+        data
+    }
+    fn settings(&self) -> GraphSettings {
+        GraphSettings::new()
+            .title(
+                "Protocol errors",
+                Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan),
+            )
+            .x_title("Time", Style::default().fg(Color::White))
+            .y_title("Rate", Style::default().fg(Color::White))
+            .x_labels(self.m.x_bounds_labels(conv_t, 4))
+            .y_labels(self.m.y_bounds_labels(|v| format!("{:.1}/s", v), 4))
+    }
+    fn monitor(&self) -> &Screen {
+        &self.m
+    }
+}
+
+impl InfoGraphWidget for Monitor<NetStatStat> {
+    const DIRECTION: Direction = Direction::Horizontal;
+    const CONSTRAINTS: [Constraint; 2] = [Constraint::Percentage(30), Constraint::Min(70)];
+
+    fn render_extra_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let stat = match self.stats.first() {
+            Some(stat) => stat,
+            None => return,
+        };
+        match &stat.conntrack {
+            Some(conntrack) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(CONNTRACK_GAUGE_HEIGHT), Constraint::Min(0)])
+                    .split(area);
+                f.render_widget(NetStatStat::conntrack_gauge(conntrack), chunks[0]);
+                f.render_widget(stat.info(), chunks[1]);
+            }
+            None => f.render_widget(stat.info(), area),
+        }
+    }
+}
+
+impl Monitor<NetStatStat> {
+    pub fn new() -> Result<Monitor<NetStatStat>> {
+        Ok(Monitor {
+            stats: vec![NetStatStat::new(NetStat::read()?)],
+            m: Screen::new(X_AXIS, Y_AXIS),
+        })
+    }
+
+    pub fn graph_loop() -> Result<()> {
+        let mut monitor = Monitor::<NetStatStat>::new()?;
+        single_widget_loop(&mut monitor, Config::new(TICK_RATE))
+    }
+}
@@ -0,0 +1,148 @@
+use super::{
+    common::{
+        single_widget_loop, Counter, DataSeries, GraphSettings, GraphWidget, InfoGraphWidget, Monitor, Screen,
+        Statistic,
+    },
+    events::Config,
+};
+use crate::{
+    linux::rapl::{rapl_domains, RaplDomain},
+    util::{conv_t, conv_w, random_color},
+};
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    widgets::{Dataset, Row, Table},
+    Frame,
+};
+
+const X_AXIS: (f64, f64) = (0., 30.0);
+const Y_AXIS: (f64, f64) = (0., 10.);
+const TICK_RATE: u64 = 500;
+const POWER_INFO_HEADERS: &[&str] = &["domain", "power", "Σenergy"];
+
+#[derive(Debug)]
+// Power consumption of a single RAPL domain
+pub struct RaplStat {
+    domain: RaplDomain,
+    energy_uj: Counter,
+    data: DataSeries,
+    watts: f64,
+    // Energy in joules consumed since the start of monitoring
+    total: f64,
+}
+impl From<RaplDomain> for RaplStat {
+    fn from(domain: RaplDomain) -> Self {
+        Self {
+            energy_uj: domain.counter(),
+            domain,
+            data: DataSeries::new(random_color(Some(20))),
+            watts: 0.,
+            total: 0.,
+        }
+    }
+}
+impl Statistic for RaplStat {
+    fn update(&mut self, m: &mut Screen) -> Result<()> {
+        self.domain
+            .update()
+            .map_err(|e| anyhow!("Failed to update RAPL domain `{}` - {}", self.domain.name, e))?;
+        let energy = match self.energy_uj.update(self.domain.energy_uj).value() {
+            Some(energy_uj) => energy_uj as f64 / 1_000_000.,
+            None => return Ok(()),
+        };
+
+        self.total += energy;
+        self.watts = energy / m.elapsed_since_last();
+        self.data.add(m.elapsed_since_start(), self.watts);
+        m.set_if_y_max(self.watts + 1.);
+
+        Ok(())
+    }
+    fn pop(&mut self) -> f64 {
+        let removed = self.data.pop();
+        if let Some(point) = self.data.first() {
+            return point.0 - removed.0;
+        }
+        0.
+    }
+    fn name(&self) -> &str {
+        &self.domain.name
+    }
+    fn oldest(&self) -> Option<f64> {
+        self.data.first().map(|(time, _)| time)
+    }
+}
+
+impl GraphWidget for Monitor<RaplStat> {
+    fn datasets(&self) -> Vec<Dataset> {
+        let mut data = Vec::new();
+        for stat in &self.stats {
+            data.push(
+                Dataset::default()
+                    .name(stat.name())
+                    .marker(symbols::Marker::Braille)
+                    .style(Style::default().fg(stat.data.color))
+                    .data(&stat.data.dataset()),
+            );
+        }
+        data
+    }
+    fn settings(&self) -> GraphSettings {
+        GraphSettings::new()
+            .title(
+                "Power consumption",
+                Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan),
+            )
+            .x_title("Time", Style::default().fg(Color::White))
+            .y_title("Power", Style::default().fg(Color::White))
+            .x_labels(self.m.x_bounds_labels(conv_t, 4))
+            .y_labels(self.m.y_bounds_labels(conv_w, 4))
+    }
+    fn monitor(&self) -> &Screen {
+        &self.m
+    }
+}
+
+impl InfoGraphWidget for Monitor<RaplStat> {
+    const DIRECTION: Direction = Direction::Horizontal;
+    const CONSTRAINTS: [Constraint; 2] = [Constraint::Percentage(20), Constraint::Min(80)];
+
+    fn render_extra_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let data = self.stats.iter().map(|s| {
+            Row::StyledData(
+                vec![s.domain.name.clone(), conv_w(s.watts), format!("{:.1} J", s.total)].into_iter(),
+                Style::default().fg(s.data.color),
+            )
+        });
+
+        let table = Table::new(POWER_INFO_HEADERS.iter(), data)
+            .widths(&[
+                Constraint::Percentage(40),
+                Constraint::Percentage(30),
+                Constraint::Percentage(30),
+            ])
+            .header_gap(1)
+            .column_spacing(1);
+
+        f.render_widget(table, area);
+    }
+}
+
+impl Monitor<RaplStat> {
+    pub fn new(sysfs: &Path) -> Result<Monitor<RaplStat>> {
+        Ok(Monitor {
+            stats: rapl_domains(sysfs)?.into_iter().map(RaplStat::from).collect(),
+            m: Screen::new(X_AXIS, Y_AXIS),
+        })
+    }
+
+    pub fn graph_loop(sysfs: &Path) -> Result<()> {
+        let mut monitor = Self::new(sysfs)?;
+        single_widget_loop(&mut monitor, Config::new(TICK_RATE))
+    }
+}
@@ -0,0 +1,211 @@
+use super::{
+    common::{
+        kv_span, single_widget_loop, spans_from, DataSeries, GraphSettings, GraphWidget, InfoGraphWidget, Monitor,
+        Screen, StatefulWidget, Statistic,
+    },
+    events::Config,
+};
+use crate::{
+    linux::pressure::{Pressure, SystemPressure},
+    util::{conv_p, conv_t, random_color},
+};
+use anyhow::{anyhow, Result};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Span, Spans},
+    widgets::{Dataset, Paragraph},
+    Frame,
+};
+
+const X_AXIS: (f64, f64) = (0., 30.0);
+const Y_AXIS: (f64, f64) = (0., 10.);
+const TICK_RATE: u64 = 1000;
+const RESOURCES: &[&str] = &["cpu", "memory", "io"];
+
+#[derive(Debug)]
+// A single average of a pressure file like `some avg10`
+struct PressureSeries {
+    name: &'static str,
+    data: DataSeries,
+    last: f64,
+}
+
+#[derive(Debug)]
+// Pressure stall averages of a single resource
+pub struct PressureStat {
+    resource: &'static str,
+    series: Vec<PressureSeries>,
+}
+impl Statistic for PressureStat {
+    fn update(&mut self, m: &mut Screen) -> Result<()> {
+        let pressure = SystemPressure::resource(self.resource)
+            .map_err(|e| anyhow!("Failed to update `{}` pressure - {}", self.resource, e))?;
+        let time = m.elapsed_since_start();
+
+        for (series, (_, value)) in self.series.iter_mut().zip(pressure.averages()) {
+            series.last = value;
+            series.data.add(time, value);
+            m.set_if_y_max(value + 1.);
+        }
+
+        Ok(())
+    }
+    fn pop(&mut self) -> f64 {
+        let mut removed = (0., 0.);
+        for (i, series) in self.series.iter_mut().enumerate() {
+            let point = series.data.pop();
+            if i == 0 {
+                removed = point;
+            }
+        }
+
+        if let Some(point) = self.series.first().and_then(|s| s.data.first()) {
+            return point.0 - removed.0;
+        }
+        0.
+    }
+    fn name(&self) -> &str {
+        self.resource
+    }
+}
+impl PressureStat {
+    fn new(resource: &'static str, pressure: Pressure) -> Self {
+        Self {
+            resource,
+            series: pressure
+                .averages()
+                .into_iter()
+                .map(|(name, last)| PressureSeries {
+                    name,
+                    data: DataSeries::new(random_color(Some(20))),
+                    last,
+                })
+                .collect(),
+        }
+    }
+
+    fn info(&self) -> Paragraph {
+        let mut spans = vec![Spans::from(Span::styled(
+            self.resource,
+            Style::default().add_modifier(Modifier::BOLD).fg(Color::Green),
+        ))];
+        spans.extend(self.series.iter().map(|s| {
+            spans_from(vec![kv_span(
+                format!(" {:11}: ", s.name),
+                conv_p(s.last),
+                s.data.color,
+                true,
+            )])
+        }));
+        Paragraph::new(spans)
+    }
+}
+
+impl GraphWidget for Monitor<PressureStat> {
+    fn datasets(&self) -> Vec<Dataset> {
+        let mut data = Vec::new();
+        for stat in &self.stats {
+            for series in &stat.series {
+                data.push(
+                    Dataset::default()
+                        .name(series.name)
+                        .marker(symbols::Marker::Braille)
+                        .style(Style::default().fg(series.data.color))
+                        .data(&series.data.dataset()),
+                );
+            }
+        }
+        data
+    }
+    fn settings(&self) -> GraphSettings {
+        let title = self.stats.first().map(|s| s.resource).unwrap_or_default();
+        GraphSettings::new()
+            .title(
+                format!("Pressure - {}", title),
+                Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan),
+            )
+            .x_title("Time", Style::default().fg(Color::White))
+            .y_title("Stalled", Style::default().fg(Color::White))
+            .x_labels(self.m.x_bounds_labels(conv_t, 4))
+            .y_labels(self.m.y_bounds_labels(conv_p, 4))
+    }
+    fn monitor(&self) -> &Screen {
+        &self.m
+    }
+}
+
+impl InfoGraphWidget for Monitor<PressureStat> {
+    const DIRECTION: Direction = Direction::Horizontal;
+    const CONSTRAINTS: [Constraint; 2] = [Constraint::Percentage(20), Constraint::Min(80)];
+
+    fn render_extra_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        if let Some(stat) = self.stats.first() {
+            f.render_widget(stat.info(), area);
+        }
+    }
+}
+
+impl Monitor<PressureStat> {
+    pub fn new(resource: &'static str) -> Result<Monitor<PressureStat>> {
+        let pressure = SystemPressure::resource(resource)?;
+        Ok(Monitor {
+            stats: vec![PressureStat::new(resource, pressure)],
+            m: Screen::new(X_AXIS, Y_AXIS),
+        })
+    }
+}
+
+/// Groups graphs of all available resources on one screen
+pub struct PressureMonitor {
+    monitors: Vec<Monitor<PressureStat>>,
+}
+
+impl StatefulWidget for PressureMonitor {
+    fn update(&mut self) -> Result<()> {
+        for monitor in &mut self.monitors {
+            StatefulWidget::update(monitor)?;
+        }
+        Ok(())
+    }
+    fn render_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let count = self.monitors.len().max(1);
+        let constraints = self
+            .monitors
+            .iter()
+            .map(|_| Constraint::Ratio(1, count as u32))
+            .collect::<Vec<Constraint>>();
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+
+        self.monitors
+            .iter()
+            .enumerate()
+            .for_each(|(i, m)| StatefulWidget::render_widget(m, f, chunks[i]));
+    }
+}
+
+impl PressureMonitor {
+    pub fn new() -> Result<PressureMonitor> {
+        let monitors = RESOURCES
+            .iter()
+            .filter_map(|r| Monitor::<PressureStat>::new(*r).ok())
+            .collect::<Vec<_>>();
+
+        if monitors.is_empty() {
+            // Return the actual reason why pressure is unavailable
+            SystemPressure::read()?;
+        }
+
+        Ok(PressureMonitor { monitors })
+    }
+
+    pub fn graph_loop() -> Result<()> {
+        let mut monitor = PressureMonitor::new()?;
+        single_widget_loop(&mut monitor, Config::new(TICK_RATE))
+    }
+}
@@ -1,9 +1,11 @@
 use super::{
-    common::{single_widget_loop, StatefulWidget},
+    common::{single_widget_loop, StatefulWidget, RESCAN_INTERVAL},
     events::Config,
 };
+use crate::linux::ports::ListeningPorts;
 use anyhow::Result;
 use rsys::linux::ps::{processes, Process};
+use std::time::{Duration, Instant};
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
@@ -12,10 +14,13 @@ use tui::{
     Frame,
 };
 
-const PS_HEADERS: &[&str] = &["pid", "name", "state", "vsize", "rss", "utime", "stime"];
+const PS_HEADERS: &[&str] = &["pid", "name", "state", "vsize", "rss", "utime", "stime", "ports"];
 
 pub struct ProcessMonitor {
     processes: Vec<Process>,
+    ports: ListeningPorts,
+    // Reading ports walks fds of every process so it's done less often than updates
+    last_ports_scan: Instant,
 }
 
 impl StatefulWidget for ProcessMonitor {
@@ -23,6 +28,11 @@ impl StatefulWidget for ProcessMonitor {
         for process in &mut self.processes {
             process.stat.update()?;
         }
+        if self.last_ports_scan.elapsed() >= Duration::from_secs_f64(RESCAN_INTERVAL) {
+            // Without permissions to read fds of other users only own processes have ports
+            self.ports = ListeningPorts::read().unwrap_or_default();
+            self.last_ports_scan = Instant::now();
+        }
         Ok(())
     }
     fn render_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
@@ -39,6 +49,8 @@ impl ProcessMonitor {
     pub fn new() -> Result<ProcessMonitor> {
         Ok(ProcessMonitor {
             processes: processes()?,
+            ports: ListeningPorts::read().unwrap_or_default(),
+            last_ports_scan: Instant::now(),
         })
     }
 
@@ -53,6 +65,11 @@ impl ProcessMonitor {
                     s.stat.rss.to_string(),
                     s.stat.utime.to_string(),
                     s.stat.stime.to_string(),
+                    self.ports
+                        .of_pid(s.stat.pid)
+                        .map(|p| p.short())
+                        .collect::<Vec<String>>()
+                        .join(","),
                 ]
                 .into_iter(),
                 Style::default(),
@@ -60,13 +77,14 @@ impl ProcessMonitor {
         });
 
         let table = Table::new(PS_HEADERS.iter(), data).widths(&[
+            Constraint::Percentage(10),
             Constraint::Percentage(14),
-            Constraint::Percentage(14),
-            Constraint::Percentage(14),
-            Constraint::Percentage(14),
-            Constraint::Percentage(14),
-            Constraint::Percentage(14),
-            Constraint::Percentage(14),
+            Constraint::Percentage(8),
+            Constraint::Percentage(12),
+            Constraint::Percentage(12),
+            Constraint::Percentage(10),
+            Constraint::Percentage(10),
+            Constraint::Percentage(24),
         ]);
 
         f.render_widget(table, area);
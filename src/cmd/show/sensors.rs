@@ -0,0 +1,171 @@
+use super::{
+    common::{single_widget_loop, DataSeries, GraphSettings, GraphWidget, InfoGraphWidget, Monitor, Screen, Statistic},
+    events::Config,
+};
+use crate::{
+    linux::sensors::{Sensor, SensorKind, Sensors},
+    util::{conv_t, random_color},
+};
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    widgets::{Dataset, Row, Table},
+    Frame,
+};
+
+const X_AXIS: (f64, f64) = (0., 30.0);
+const Y_AXIS: (f64, f64) = (0., 60.);
+const TICK_RATE: u64 = 1000;
+const SENSOR_INFO_HEADERS: &[&str] = &["sensor", "value", "crit"];
+// Ratios of value to critical threshold at which readings change color
+const WARN_RATIO: f64 = 0.75;
+const CRIT_RATIO: f64 = 0.9;
+
+#[derive(Debug)]
+// A single hardware sensor reading
+pub struct SensorStat {
+    name: String,
+    sensor: Sensor,
+    data: DataSeries,
+}
+impl From<Sensor> for SensorStat {
+    fn from(sensor: Sensor) -> Self {
+        Self {
+            name: sensor.name(),
+            sensor,
+            data: DataSeries::new(random_color(Some(20))),
+        }
+    }
+}
+impl Statistic for SensorStat {
+    fn update(&mut self, m: &mut Screen) -> Result<()> {
+        self.sensor
+            .update()
+            .map_err(|e| anyhow!("Failed to update sensor `{}` - {}", self.name, e))?;
+        self.data.add(m.elapsed_since_start(), self.sensor.value);
+
+        if self.is_temperature() {
+            m.set_if_y_max(self.sensor.value + 5.);
+        }
+
+        Ok(())
+    }
+    fn pop(&mut self) -> f64 {
+        let removed = self.data.pop();
+        if let Some(point) = self.data.first() {
+            return point.0 - removed.0;
+        }
+        0.
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+impl SensorStat {
+    fn is_temperature(&self) -> bool {
+        self.sensor.kind == SensorKind::Temperature
+    }
+
+    /// Color of the reading depending on how close it is to the critical threshold
+    fn threshold_color(&self) -> Color {
+        if self.sensor.alarm {
+            return Color::Red;
+        }
+        match self.sensor.threshold_ratio() {
+            Some(ratio) if ratio >= CRIT_RATIO => Color::Red,
+            Some(ratio) if ratio >= WARN_RATIO => Color::Yellow,
+            Some(_) => Color::Green,
+            None => Color::White,
+        }
+    }
+}
+
+impl GraphWidget for Monitor<SensorStat> {
+    fn datasets(&self) -> Vec<Dataset> {
+        self.stats
+            .iter()
+            .filter(|s| s.is_temperature())
+            .map(|s| {
+                Dataset::default()
+                    .name(s.name())
+                    .marker(symbols::Marker::Braille)
+                    .style(Style::default().fg(s.data.color))
+                    .data(&s.data.dataset())
+            })
+            .collect()
+    }
+    fn settings(&self) -> GraphSettings {
+        GraphSettings::new()
+            .title(
+                "Temperatures",
+                Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan),
+            )
+            .x_title("Time", Style::default().fg(Color::White))
+            .y_title("Temperature", Style::default().fg(Color::White))
+            .x_labels(self.m.x_bounds_labels(conv_t, 4))
+            .y_labels(self.m.y_bounds_labels(|t| SensorKind::Temperature.fmt_value(t), 4))
+    }
+    fn monitor(&self) -> &Screen {
+        &self.m
+    }
+}
+
+impl InfoGraphWidget for Monitor<SensorStat> {
+    const DIRECTION: Direction = Direction::Horizontal;
+    const CONSTRAINTS: [Constraint; 2] = [Constraint::Percentage(30), Constraint::Min(70)];
+
+    fn render_extra_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let data = self.stats.iter().map(|s| {
+            let kind = s.sensor.kind;
+            Row::StyledData(
+                vec![
+                    s.name.clone(),
+                    kind.fmt_value(s.sensor.value),
+                    s.sensor.crit.map(|c| kind.fmt_value(c)).unwrap_or_default(),
+                ]
+                .into_iter(),
+                Style::default().fg(s.threshold_color()),
+            )
+        });
+
+        let table = Table::new(SENSOR_INFO_HEADERS.iter(), data)
+            .widths(&[
+                Constraint::Percentage(50),
+                Constraint::Percentage(25),
+                Constraint::Percentage(25),
+            ])
+            .header_gap(1)
+            .column_spacing(1);
+
+        f.render_widget(table, area);
+    }
+}
+
+impl Monitor<SensorStat> {
+    pub fn new(sysfs: &Path) -> Result<Monitor<SensorStat>> {
+        let mut stats = Sensors::read(sysfs)?
+            .0
+            .into_iter()
+            .map(SensorStat::from)
+            .collect::<Vec<SensorStat>>();
+        if stats.is_empty() {
+            return Err(anyhow!("No hardware sensors found in `{}`", sysfs.display()));
+        }
+        // Temperatures first as they are the ones graphed
+        stats.sort_by_key(|s| !s.is_temperature());
+
+        Ok(Monitor {
+            stats,
+            m: Screen::new(X_AXIS, Y_AXIS),
+        })
+    }
+
+    pub fn graph_loop(sysfs: &Path) -> Result<()> {
+        let mut monitor = Self::new(sysfs)?;
+        single_widget_loop(&mut monitor, Config::new(TICK_RATE))
+    }
+}
@@ -0,0 +1,166 @@
+use super::{
+    common::{
+        kv_span, single_widget_loop, spans_from, DataSeries, GraphSettings, GraphWidget, InfoGraphWidget, Monitor,
+        Screen, Statistic,
+    },
+    events::Config,
+};
+use crate::{
+    linux::socket::{SocketState, Sockets},
+    util::{conv_t, random_color},
+};
+use anyhow::{anyhow, Result};
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Span, Spans},
+    widgets::{Dataset, Paragraph},
+    Frame,
+};
+
+const X_AXIS: (f64, f64) = (0., 30.0);
+const Y_AXIS: (f64, f64) = (0., 10.);
+const TICK_RATE: u64 = 1000;
+
+#[derive(Debug)]
+// Number of TCP sockets in a single state
+struct StateSeries {
+    state: SocketState,
+    name: String,
+    data: DataSeries,
+    last: usize,
+}
+
+#[derive(Debug)]
+// Count of TCP sockets of both IPv4 and IPv6 in each state
+pub struct SocketStat {
+    series: Vec<StateSeries>,
+    total: usize,
+}
+impl Statistic for SocketStat {
+    fn update(&mut self, m: &mut Screen) -> Result<()> {
+        let counts = Sockets::read_tcp()
+            .map_err(|e| anyhow!("Failed to read TCP sockets - {}", e))?
+            .count_by_state();
+        let time = m.elapsed_since_start();
+
+        self.total = counts.values().sum();
+        for series in &mut self.series {
+            series.last = counts.get(&series.state).copied().unwrap_or_default();
+            series.data.add(time, series.last as f64);
+            m.set_if_y_max(series.last as f64 + 1.);
+        }
+
+        Ok(())
+    }
+    fn pop(&mut self) -> f64 {
+        let mut removed = (0., 0.);
+        for (i, series) in self.series.iter_mut().enumerate() {
+            let point = series.data.pop();
+            if i == 0 {
+                removed = point;
+            }
+        }
+
+        if let Some(point) = self.series.first().and_then(|s| s.data.first()) {
+            return point.0 - removed.0;
+        }
+        0.
+    }
+    fn name(&self) -> &str {
+        "tcp"
+    }
+}
+impl SocketStat {
+    fn new() -> Self {
+        Self {
+            series: SocketState::TCP
+                .iter()
+                .map(|state| StateSeries {
+                    state: *state,
+                    name: state.to_string(),
+                    data: DataSeries::new(random_color(Some(20))),
+                    last: 0,
+                })
+                .collect(),
+            total: 0,
+        }
+    }
+
+    fn info(&self) -> Paragraph {
+        let mut spans = vec![Spans::from(Span::styled(
+            format!("TCP sockets: {}", self.total),
+            Style::default().add_modifier(Modifier::BOLD).fg(Color::Green),
+        ))];
+        spans.extend(self.series.iter().map(|s| {
+            spans_from(vec![kv_span(
+                format!(" {:12}: ", s.name),
+                s.last.to_string(),
+                s.data.color,
+                true,
+            )])
+        }));
+        Paragraph::new(spans)
+    }
+}
+
+impl GraphWidget for Monitor<SocketStat> {
+    fn datasets(&self) -> Vec<Dataset> {
+        let mut data = Vec::new();
+        for stat in &self.stats {
+            for series in &stat.series {
+                data.push(
+                    Dataset::default()
+                        .name(series.name.as_str())
+                        .marker(symbols::Marker::Braille)
+                        .style(Style::default().fg(series.data.color))
+                        .data(&series.data.dataset()),
+                );
+            }
+        }
+        data
+    }
+    fn settings(&self) -> GraphSettings {
+        GraphSettings::new()
+            .title(
+                "TCP connections",
+                Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan),
+            )
+            .x_title("Time", Style::default().fg(Color::White))
+            .y_title("Sockets", Style::default().fg(Color::White))
+            .x_labels(self.m.x_bounds_labels(conv_t, 4))
+            .y_labels(self.m.y_bounds_labels(|v| format!("{:.0}", v), 4))
+    }
+    fn monitor(&self) -> &Screen {
+        &self.m
+    }
+}
+
+impl InfoGraphWidget for Monitor<SocketStat> {
+    const DIRECTION: Direction = Direction::Horizontal;
+    const CONSTRAINTS: [Constraint; 2] = [Constraint::Percentage(20), Constraint::Min(80)];
+
+    fn render_extra_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        if let Some(stat) = self.stats.first() {
+            f.render_widget(stat.info(), area);
+        }
+    }
+}
+
+impl Monitor<SocketStat> {
+    pub fn new() -> Result<Monitor<SocketStat>> {
+        // Fail early if sockets can't be read at all
+        Sockets::read_tcp()?;
+        Ok(Monitor {
+            stats: vec![SocketStat::new()],
+            m: Screen::new(X_AXIS, Y_AXIS),
+        })
+    }
+
+    pub fn graph_loop() -> Result<()> {
+        let mut monitor = Monitor::<SocketStat>::new()?;
+        single_widget_loop(&mut monitor, Config::new(TICK_RATE))
+    }
+}
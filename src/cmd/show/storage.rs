@@ -1,26 +1,157 @@
 use super::{
     common::{
-        single_widget_loop, DataSeries, GraphSettings, GraphWidget, InfoGraphWidget, Monitor, RxTx, Screen, Statistic,
+        single_widget_loop, Counter, DataSeries, GraphSettings, GraphWidget, Hotplug, InfoGraphWidget, Monitor, RxTx,
+        Screen, Statistic, Updatable, RESCAN_INTERVAL,
     },
     events::Config,
 };
-use crate::util::{conv_fbs, conv_t, random_color};
-use anyhow::{anyhow, Result};
+use crate::{
+    linux::{
+        block::SECTOR_SIZE,
+        md::{MdArray, MdArrays},
+        SYSFS,
+    },
+    util::{conv_fbs, conv_p, conv_t, random_color},
+};
+use anyhow::{anyhow, Error, Result};
 use rsys::linux::storage::{storage_devices_info, BlockStorageInfo};
+use std::{path::Path, str::FromStr};
+use termion::event::Key;
 use tui::{
     backend::Backend,
-    layout::{Constraint, Direction, Rect},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     symbols,
-    widgets::{Dataset, Row, Table},
+    widgets::{Block, Borders, Dataset, Gauge, Row, Table},
     Frame,
 };
 
 const X_AXIS: (f64, f64) = (0., 30.0);
 const Y_AXIS: (f64, f64) = (0., 100.);
 const TICK_RATE: u64 = 200;
-const SECTOR_SIZE: f64 = 512.;
-const STORAGE_INFO_HEADERS: &[&str] = &["name", "rx/s", "wx/s", "Σrx", "Σwx"];
+const STORAGE_INFO_HEADERS: &[&str] = &[
+    "name", "rx/s", "wx/s", "Σrx", "Σwx", "r/s", "w/s", "r_await", "w_await", "%util", "inflight",
+];
+const VIEW_KEY: Key = Key::Char('v');
+const CLASS_BLOCK_DIR: &str = "class/block";
+// Height of a single md array gauge including its borders
+const MD_GAUGE_HEIGHT: u16 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Metric graphed by the storage dashboard
+pub enum StorageView {
+    /// Bytes read and written per second
+    Throughput,
+    /// Completed read and write requests per second
+    Iops,
+    /// Average time in milliseconds a request took to complete including time in queue
+    Await,
+    /// Percentage of time the device had requests in flight
+    Util,
+    /// Number of requests in flight
+    Queue,
+}
+impl StorageView {
+    pub const VARIANTS: &'static [&'static str] = &["throughput", "iops", "await", "util", "queue"];
+
+    /// Returns the view displayed after this one when switching views
+    fn next(self) -> StorageView {
+        match self {
+            StorageView::Throughput => StorageView::Iops,
+            StorageView::Iops => StorageView::Await,
+            StorageView::Await => StorageView::Util,
+            StorageView::Util => StorageView::Queue,
+            StorageView::Queue => StorageView::Throughput,
+        }
+    }
+
+    fn y_title(self) -> &'static str {
+        match self {
+            StorageView::Throughput => "r/w speed",
+            StorageView::Iops => "r/w iops",
+            StorageView::Await => "r/w await",
+            StorageView::Util => "utilization",
+            StorageView::Queue => "in flight",
+        }
+    }
+
+    fn y_label(self, value: f64) -> String {
+        match self {
+            StorageView::Throughput => conv_fbs(value),
+            StorageView::Iops => format!("{:.0}/s", value),
+            StorageView::Await => format!("{:.1}ms", value),
+            StorageView::Util => conv_p(value),
+            StorageView::Queue => format!("{:.1}", value),
+        }
+    }
+
+    // Lowest upper bound of y axis so that an idle device doesn't produce a flat zero range
+    fn min_y_max(self) -> f64 {
+        match self {
+            StorageView::Throughput | StorageView::Util => 100.,
+            StorageView::Iops => 10.,
+            StorageView::Await | StorageView::Queue => 1.,
+        }
+    }
+}
+impl FromStr for StorageView {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "throughput" => Ok(StorageView::Throughput),
+            "iops" => Ok(StorageView::Iops),
+            "await" => Ok(StorageView::Await),
+            "util" => Ok(StorageView::Util),
+            "queue" => Ok(StorageView::Queue),
+            _ => Err(anyhow!("Unknown storage view `{}`", s)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+// Cumulative counters from block device stat needed to calculate rates between updates
+struct IoCounters {
+    sectors: RxTx<Counter>,
+    ios: RxTx<Counter>,
+    ticks: RxTx<Counter>,
+    io_ticks: Counter,
+    in_flight: u64,
+}
+
+// Change of block device counters between two updates
+struct IoDeltas {
+    bytes: (u64, u64),
+    ios: (u64, u64),
+    ticks: (u64, u64),
+    io_ticks: u64,
+}
+
+impl IoCounters {
+    fn new(info: &BlockStorageInfo) -> Self {
+        let mut counters = IoCounters::default();
+        counters.update(info);
+        counters
+    }
+
+    // Records current counters of the device returning their change since previous update.
+    // Returns None if there is no previous sample or the counters were reset.
+    fn update(&mut self, info: &BlockStorageInfo) -> Option<IoDeltas> {
+        let stat = info.stat.as_ref()?;
+        self.in_flight = stat.in_flight as u64;
+        let sectors = self.sectors.update(stat.read_sectors as u64, stat.write_sectors as u64);
+        let ios = self.ios.update(stat.read_ios as u64, stat.write_ios as u64);
+        let ticks = self.ticks.update(stat.read_ticks as u64, stat.write_ticks as u64);
+        let io_ticks = self.io_ticks.update(stat.io_ticks as u64);
+
+        let (read_sectors, write_sectors) = sectors.values()?;
+        Some(IoDeltas {
+            bytes: (read_sectors * SECTOR_SIZE, write_sectors * SECTOR_SIZE),
+            ios: ios.values()?,
+            ticks: ticks.values()?,
+            io_ticks: io_ticks.value()?,
+        })
+    }
+}
 
 #[derive(Debug)]
 // Stats of a single block storage device
@@ -28,44 +159,71 @@ pub struct StorageSpeedStat {
     name: String,
     color: Color,
     device: BlockStorageInfo,
+    counters: IoCounters,
     data: RxTx<DataSeries>,
+    iops_data: RxTx<DataSeries>,
+    await_data: RxTx<DataSeries>,
+    util_data: DataSeries,
+    queue_data: DataSeries,
     speed: RxTx<f64>,
     total: RxTx<f64>,
+    iops: RxTx<f64>,
+    await_ms: RxTx<f64>,
+    util: f64,
+    gone: bool,
 }
 impl From<BlockStorageInfo> for StorageSpeedStat {
     fn from(info: BlockStorageInfo) -> Self {
+        let color = random_color(Some(20));
+        let series = || RxTx((DataSeries::new(color), DataSeries::new(color)));
         Self {
             name: info.dev.to_string(),
-            color: random_color(Some(20)),
+            color,
+            counters: IoCounters::new(&info),
             device: info,
-            data: RxTx((
-                DataSeries::new(random_color(Some(20))),
-                DataSeries::new(random_color(Some(20))),
-            )),
+            data: series(),
+            iops_data: series(),
+            await_data: series(),
+            util_data: DataSeries::new(color),
+            queue_data: DataSeries::new(color),
             speed: RxTx::default(),
             total: RxTx::default(),
+            iops: RxTx::default(),
+            await_ms: RxTx::default(),
+            util: 0.,
+            gone: false,
         }
     }
 }
 impl Statistic for StorageSpeedStat {
     fn update(&mut self, m: &mut Screen) -> Result<()> {
-        let (rx_before, wx_before) = self.sectors();
         let time_delta = m.elapsed_since_last();
 
-        self.device
-            .update_stats()
-            .map_err(|e| anyhow!("Failed to update block device `{}` stats - {}", self.name, e))?;
-
-        let (rx_after, wx_after) = self.sectors();
+        if let Err(e) = self.device.update_stats() {
+            // Device was unplugged in the meantime
+            if !Path::new(SYSFS).join(CLASS_BLOCK_DIR).join(&self.name).exists() {
+                self.gone = true;
+                return Ok(());
+            }
+            return Err(anyhow!("Failed to update block device `{}` stats - {}", self.name, e));
+        }
 
-        let rx_delta = rx_after - rx_before;
-        let wx_delta = wx_after - wx_before;
+        // Skip the sample if counters were reset, for example by a driver reload
+        let delta = match self.counters.update(&self.device) {
+            Some(delta) => delta,
+            None => return Ok(()),
+        };
+        let (rx_delta, wx_delta) = (delta.bytes.0 as f64, delta.bytes.1 as f64);
+        let (read_ios, write_ios) = delta.ios;
 
         self.total.inc(rx_delta, wx_delta);
         self.speed = RxTx((rx_delta / time_delta, wx_delta / time_delta));
-
-        m.set_if_y_max(*self.speed.rx() + 100.);
-        m.set_if_y_max(*self.speed.tx() + 100.);
+        self.iops = RxTx((read_ios as f64 / time_delta, write_ios as f64 / time_delta));
+        // Same as `r_await` and `w_await` of `iostat -x`
+        let avg_ticks = |ticks: u64, ios: u64| if ios == 0 { 0. } else { ticks as f64 / ios as f64 };
+        self.await_ms = RxTx((avg_ticks(delta.ticks.0, read_ios), avg_ticks(delta.ticks.1, write_ios)));
+        // io_ticks are milliseconds spent doing I/O
+        self.util = (100. * delta.io_ticks as f64 / (time_delta * 1000.)).min(100.);
 
         self.add_current(m.elapsed_since_start());
 
@@ -74,6 +232,12 @@ impl Statistic for StorageSpeedStat {
     fn pop(&mut self) -> f64 {
         let removed = self.data.rx_mut().pop();
         self.data.tx_mut().pop();
+        self.iops_data.rx_mut().pop();
+        self.iops_data.tx_mut().pop();
+        self.await_data.rx_mut().pop();
+        self.await_data.tx_mut().pop();
+        self.util_data.pop();
+        self.queue_data.pop();
 
         if let Some(point) = self.data.rx().first() {
             return point.0 - removed.0;
@@ -83,113 +247,265 @@ impl Statistic for StorageSpeedStat {
     fn name(&self) -> &str {
         &self.name
     }
+    fn is_gone(&self) -> bool {
+        self.gone
+    }
+    fn oldest(&self) -> Option<f64> {
+        self.data.rx().first().map(|(time, _)| time)
+    }
+}
+impl Hotplug for StorageSpeedStat {
+    type Device = BlockStorageInfo;
+
+    fn scan() -> Result<Vec<BlockStorageInfo>> {
+        Ok(storage_devices_info()
+            .map_err(|e| anyhow!("Failed to get storage devices info - {}", e))?
+            .into_iter()
+            .collect())
+    }
+    fn device_name(info: &BlockStorageInfo) -> String {
+        info.dev.to_string()
+    }
+    fn set_gone(&mut self) {
+        self.gone = true;
+    }
 }
 impl StorageSpeedStat {
-    fn sectors(&mut self) -> (f64, f64) {
-        if let Some(stat) = &self.device.stat {
-            (
-                stat.read_sectors as f64 * SECTOR_SIZE,
-                stat.write_sectors as f64 * SECTOR_SIZE,
-            )
+    // Name of the device as displayed in legend and table
+    fn label(&self) -> String {
+        if self.gone {
+            format!("{} (gone)", self.name)
         } else {
-            (0., 0.)
+            self.name.clone()
         }
     }
 
+    // Style of legend and table row, removed devices are greyed out
+    fn style(&self) -> Style {
+        Style::default().fg(if self.gone { Color::DarkGray } else { self.color })
+    }
+
     fn add_current(&mut self, time: f64) {
         self.data.rx_mut().add(time, *self.speed.rx());
         self.data.tx_mut().add(time, *self.speed.tx());
+        self.iops_data.rx_mut().add(time, *self.iops.rx());
+        self.iops_data.tx_mut().add(time, *self.iops.tx());
+        self.await_data.rx_mut().add(time, *self.await_ms.rx());
+        self.await_data.tx_mut().add(time, *self.await_ms.tx());
+        self.util_data.add(time, self.util);
+        self.queue_data.add(time, self.counters.in_flight as f64);
+    }
+
+    // Returns read and write series of view or a single series for views
+    // that don't distinguish between reads and writes
+    fn series(&self, view: StorageView) -> (&DataSeries, Option<&DataSeries>) {
+        match view {
+            StorageView::Throughput => (self.data.rx(), Some(self.data.tx())),
+            StorageView::Iops => (self.iops_data.rx(), Some(self.iops_data.tx())),
+            StorageView::Await => (self.await_data.rx(), Some(self.await_data.tx())),
+            StorageView::Util => (&self.util_data, None),
+            StorageView::Queue => (&self.queue_data, None),
+        }
     }
 }
 
-impl GraphWidget for Monitor<StorageSpeedStat> {
+/// Storage devices graph with a switchable metric
+pub struct StorageMonitor {
+    monitor: Monitor<StorageSpeedStat>,
+    view: StorageView,
+    md: MdArrays,
+    last_scan: f64,
+}
+
+impl Updatable for StorageMonitor {
+    fn update(&mut self) -> Result<()> {
+        Updatable::update(&mut self.monitor)?;
+
+        let elapsed = self.monitor.m.elapsed_since_start();
+        if elapsed - self.last_scan >= RESCAN_INTERVAL {
+            // A failed scan is retried after next interval, present devices are still updated
+            if self.monitor.rescan(|_| true).is_ok() {
+                self.monitor.stats.sort_by(|s1, s2| s1.name.cmp(&s2.name));
+            }
+            self.last_scan = elapsed;
+        }
+        self.fit_y_axis();
+        self.md = MdArrays::read(SYSFS).unwrap_or_default();
+        Ok(())
+    }
+    fn handle_key(&mut self, key: Key) {
+        if key == VIEW_KEY {
+            self.view = self.view.next();
+            self.fit_y_axis();
+        }
+    }
+}
+
+impl GraphWidget for StorageMonitor {
     fn datasets(&self) -> Vec<Dataset> {
         let mut data = Vec::new();
-        for device in &self.stats {
-            data.push(
-                Dataset::default()
-                    .name(format!("rx {}", &device.name))
-                    .marker(symbols::Marker::Dot)
-                    .style(Style::default().fg(device.color))
-                    .data(&device.data.rx().dataset()),
-            );
-            data.push(
-                Dataset::default()
-                    .name(format!("wx {}", &device.name))
-                    .marker(symbols::Marker::Braille)
-                    .style(Style::default().fg(device.color))
-                    .data(&device.data.tx().dataset()),
-            );
+        for device in &self.monitor.stats {
+            match device.series(self.view) {
+                (read, Some(write)) => {
+                    data.push(
+                        Dataset::default()
+                            .name(format!("rx {}", device.label()))
+                            .marker(symbols::Marker::Dot)
+                            .style(device.style())
+                            .data(read.dataset()),
+                    );
+                    data.push(
+                        Dataset::default()
+                            .name(format!("wx {}", device.label()))
+                            .marker(symbols::Marker::Braille)
+                            .style(device.style())
+                            .data(write.dataset()),
+                    );
+                }
+                (series, None) => data.push(
+                    Dataset::default()
+                        .name(device.label())
+                        .marker(symbols::Marker::Braille)
+                        .style(device.style())
+                        .data(series.dataset()),
+                ),
+            }
         }
         data
     }
     fn settings(&self) -> GraphSettings {
+        let view = self.view;
         GraphSettings::new()
             .title(
-                "Storage devices",
+                format!("Storage devices - {} (`v` - next view)", view.y_title()),
                 Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan),
             )
             .x_title("Time", Style::default().fg(Color::White))
-            .y_title("r/w speed", Style::default().fg(Color::White))
-            .x_labels(self.m.x_bounds_labels(conv_t, 4))
-            .y_labels(self.m.y_bounds_labels(conv_fbs, 5))
+            .y_title(view.y_title(), Style::default().fg(Color::White))
+            .x_labels(self.monitor.m.x_bounds_labels(conv_t, 4))
+            .y_labels(self.monitor.m.y_bounds_labels(|v| view.y_label(v), 5))
     }
     fn monitor(&self) -> &Screen {
-        &self.m
+        &self.monitor.m
     }
 }
 
-impl InfoGraphWidget for Monitor<StorageSpeedStat> {
+impl InfoGraphWidget for StorageMonitor {
     const DIRECTION: Direction = Direction::Horizontal;
-    const CONSTRAINTS: [Constraint; 2] = [Constraint::Percentage(20), Constraint::Min(80)];
+    const CONSTRAINTS: [Constraint; 2] = [Constraint::Percentage(45), Constraint::Min(55)];
 
     fn render_extra_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
-        let data = self.stats.iter().map(|s| {
+        // Md arrays get a gauge with sync progress under the table so that a degraded array stands out
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(MD_GAUGE_HEIGHT * self.md.0.len() as u16),
+            ])
+            .split(area);
+        let md_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(MD_GAUGE_HEIGHT); self.md.0.len()])
+            .split(chunks[1]);
+        for (array, chunk) in self.md.0.iter().zip(md_chunks) {
+            f.render_widget(StorageMonitor::md_gauge(array), chunk);
+        }
+
+        let data = self.monitor.stats.iter().map(|s| {
             Row::StyledData(
                 vec![
-                    s.name.to_string(),
+                    s.label(),
                     s.speed.rx_speed_str(),
                     s.speed.tx_speed_str(),
                     s.total.rx_bytes_str(),
                     s.total.tx_bytes_str(),
+                    format!("{:.1}", s.iops.rx()),
+                    format!("{:.1}", s.iops.tx()),
+                    format!("{:.2}", s.await_ms.rx()),
+                    format!("{:.2}", s.await_ms.tx()),
+                    format!("{:.1}", s.util),
+                    s.counters.in_flight.to_string(),
                 ]
                 .into_iter(),
-                Style::default().fg(s.color),
+                s.style(),
             )
         });
 
         let table = Table::new(STORAGE_INFO_HEADERS.iter(), data)
             .widths(&[
-                Constraint::Percentage(10),
-                Constraint::Percentage(20),
-                Constraint::Percentage(20),
-                Constraint::Percentage(16),
-                Constraint::Percentage(16),
+                Constraint::Percentage(8),
+                Constraint::Percentage(11),
+                Constraint::Percentage(11),
+                Constraint::Percentage(9),
+                Constraint::Percentage(9),
+                Constraint::Percentage(7),
+                Constraint::Percentage(7),
+                Constraint::Percentage(9),
+                Constraint::Percentage(9),
+                Constraint::Percentage(7),
+                Constraint::Percentage(8),
             ])
             .header_gap(1)
             .column_spacing(1);
 
-        f.render_widget(table, area);
+        f.render_widget(table, chunks[0]);
     }
 }
 
-impl Monitor<StorageSpeedStat> {
-    pub fn new() -> Result<Monitor<StorageSpeedStat>> {
-        Ok(Monitor {
-            stats: {
-                let mut stats = storage_devices_info()
-                    .map_err(|e| anyhow!("Failed to get storage devices info - {}", e))?
-                    .into_iter()
-                    .map(StorageSpeedStat::from)
-                    .collect::<Vec<StorageSpeedStat>>();
-                stats.sort_by(|s1, s2| s1.name.cmp(&s2.name));
-                stats
+impl StorageMonitor {
+    pub fn new(view: StorageView) -> Result<StorageMonitor> {
+        let mut stats = StorageSpeedStat::scan()?;
+        stats.sort_by(|s1, s2| s1.name.cmp(&s2.name));
+
+        Ok(StorageMonitor {
+            monitor: Monitor {
+                stats,
+                m: Screen::new(X_AXIS, Y_AXIS),
             },
-            m: Screen::new(X_AXIS, Y_AXIS),
+            view,
+            md: MdArrays::read(SYSFS).unwrap_or_default(),
+            last_scan: 0.,
         })
     }
-    pub fn graph_loop() -> Result<()> {
-        let mut monitor = Monitor::<StorageSpeedStat>::new()?;
+
+    fn md_gauge(array: &MdArray) -> Gauge {
+        let (ratio, label) = match &array.sync {
+            Some(sync) => (sync.progress / 100., array.sync_str().unwrap_or_default()),
+            None => (1., array.health()),
+        };
+        let color = if array.is_degraded() { Color::Red } else { Color::Green };
+        Gauge::default()
+            .block(
+                Block::default()
+                    .title(format!("{} {} - {}", array.name, array.level, array.health()))
+                    .borders(Borders::ALL),
+            )
+            .gauge_style(Style::default().fg(color))
+            .ratio(ratio.clamp(0., 1.))
+            .label(label)
+    }
+
+    // Scales y axis to the highest value of the current view that is still on screen
+    fn fit_y_axis(&mut self) {
+        let view = self.view;
+        let max = self
+            .monitor
+            .stats
+            .iter()
+            .flat_map(|s| {
+                let (first, second) = s.series(view);
+                first
+                    .dataset()
+                    .iter()
+                    .chain(second.map(|s| s.dataset()).unwrap_or_default())
+                    .map(|(_, y)| *y)
+            })
+            .fold(0., f64::max);
+        self.monitor.m.set_y_max((max * 1.1).max(view.min_y_max()));
+    }
+
+    pub fn graph_loop(view: StorageView) -> Result<()> {
+        let mut monitor = StorageMonitor::new(view)?;
         single_widget_loop(&mut monitor, Config::new(TICK_RATE))
     }
 }
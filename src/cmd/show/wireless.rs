@@ -0,0 +1,244 @@
+use super::{
+    common::{
+        kv_span, single_widget_loop, spans_from, Counter, DataSeries, GraphSettings, GraphWidget, InfoGraphWidget,
+        Monitor, Screen, StatefulWidget, Statistic,
+    },
+    events::Config,
+    net::{NetMonitor, NetView},
+};
+use crate::{
+    cmd::common::IfaceFilter,
+    linux::{wireless::Wireless, SYSFS},
+    util::{conv_t, random_color},
+};
+use anyhow::Result;
+use termion::event::Key;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Span, Spans},
+    widgets::{Dataset, Paragraph},
+    Frame,
+};
+
+const X_AXIS: (f64, f64) = (0., 30.0);
+// Typical range between an unusable and an excellent signal in dBm
+const Y_AXIS: (f64, f64) = (-90., -30.);
+const TICK_RATE: u64 = 300;
+
+#[derive(Debug)]
+// Signal level of a wireless interface
+pub struct SignalStat {
+    name: String,
+    wireless: Option<Wireless>,
+    data: DataSeries,
+    discarded: Counter,
+    missed_beacons: Counter,
+    // Packets discarded and beacons missed since last update
+    discarded_delta: u64,
+    missed_delta: u64,
+}
+impl Statistic for SignalStat {
+    fn update(&mut self, m: &mut Screen) -> Result<()> {
+        // The interface is missing from /proc/net/wireless while it's disconnected
+        self.wireless = Wireless::read(SYSFS, &self.name).ok();
+        let wireless = match &self.wireless {
+            Some(wireless) => wireless,
+            None => return Ok(()),
+        };
+
+        self.discarded_delta = self
+            .discarded
+            .update(wireless.discarded.total())
+            .value()
+            .unwrap_or_default();
+        self.missed_delta = self
+            .missed_beacons
+            .update(wireless.missed_beacons)
+            .value()
+            .unwrap_or_default();
+
+        let signal = wireless.signal_dbm;
+        self.data.add(m.elapsed_since_start(), signal);
+        m.set_if_y_min(signal - 5.);
+        m.set_if_y_max(signal + 5.);
+
+        Ok(())
+    }
+    fn pop(&mut self) -> f64 {
+        let removed = self.data.pop();
+        if let Some(point) = self.data.first() {
+            return point.0 - removed.0;
+        }
+        0.
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn oldest(&self) -> Option<f64> {
+        self.data.first().map(|(time, _)| time)
+    }
+}
+impl SignalStat {
+    fn new(wireless: Wireless) -> Self {
+        Self {
+            name: wireless.iface.clone(),
+            data: DataSeries::new(random_color(Some(20))),
+            discarded: Counter::new(wireless.discarded.total()),
+            missed_beacons: Counter::new(wireless.missed_beacons),
+            discarded_delta: 0,
+            missed_delta: 0,
+            wireless: Some(wireless),
+        }
+    }
+
+    fn info(&self) -> Paragraph {
+        let wireless = match &self.wireless {
+            Some(wireless) => wireless,
+            None => {
+                return Paragraph::new(vec![Spans::from(Span::styled(
+                    format!("{} disconnected", self.name),
+                    Style::default().add_modifier(Modifier::BOLD).fg(Color::Red),
+                ))])
+            }
+        };
+        // Red while packets are being discarded or beacons missed
+        let problem_color = |delta: u64| if delta > 0 { Color::Red } else { Color::White };
+        Paragraph::new(vec![
+            Spans::from(Span::styled(
+                format!("{} signal", self.name),
+                Style::default().add_modifier(Modifier::BOLD).fg(Color::Green),
+            )),
+            spans_from(vec![kv_span(
+                " sig : ".to_string(),
+                format!("{:.0} dBm", wireless.signal_dbm),
+                self.data.color,
+                true,
+            )]),
+            spans_from(vec![kv_span(
+                " nois: ".to_string(),
+                wireless
+                    .noise_dbm
+                    .map(|n| format!("{:.0} dBm", n))
+                    .unwrap_or_else(|| "-".to_string()),
+                Color::White,
+                true,
+            )]),
+            spans_from(vec![kv_span(
+                " qual: ".to_string(),
+                format!("{:.0}", wireless.link_quality),
+                Color::White,
+                true,
+            )]),
+            spans_from(vec![kv_span(
+                " disc: ".to_string(),
+                wireless.discarded.total().to_string(),
+                problem_color(self.discarded_delta),
+                true,
+            )]),
+            spans_from(vec![kv_span(
+                " beac: ".to_string(),
+                format!("{} missed", wireless.missed_beacons),
+                problem_color(self.missed_delta),
+                true,
+            )]),
+        ])
+    }
+}
+
+impl GraphWidget for Monitor<SignalStat> {
+    fn datasets(&self) -> Vec<Dataset> {
+        self.stats
+            .iter()
+            .map(|s| {
+                Dataset::default()
+                    .name(s.name())
+                    .marker(symbols::Marker::Braille)
+                    .style(Style::default().fg(s.data.color))
+                    .data(&s.data.dataset())
+            })
+            .collect()
+    }
+    fn settings(&self) -> GraphSettings {
+        GraphSettings::new()
+            .title(
+                "Signal level",
+                Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan),
+            )
+            .x_title("Time", Style::default().fg(Color::White))
+            .y_title("dBm", Style::default().fg(Color::White))
+            .x_labels(self.m.x_bounds_labels(conv_t, 4))
+            .y_labels(self.m.y_bounds_labels(|v| format!("{:.0}", v), 4))
+    }
+    fn monitor(&self) -> &Screen {
+        &self.m
+    }
+}
+
+impl InfoGraphWidget for Monitor<SignalStat> {
+    const DIRECTION: Direction = Direction::Horizontal;
+    // Same split as the network graph above so that time axes line up
+    const CONSTRAINTS: [Constraint; 2] = [Constraint::Percentage(20), Constraint::Min(80)];
+
+    fn render_extra_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        if let Some(stat) = self.stats.first() {
+            f.render_widget(stat.info(), area);
+        }
+    }
+}
+
+impl Monitor<SignalStat> {
+    pub fn new(name: &str) -> Result<Monitor<SignalStat>> {
+        Ok(Monitor {
+            stats: vec![SignalStat::new(Wireless::read(SYSFS, name)?)],
+            m: Screen::new(X_AXIS, Y_AXIS),
+        })
+    }
+}
+
+/// Throughput of a wireless interface graphed above its signal level to see
+/// how signal drops affect the connection
+pub struct WirelessMonitor {
+    net: NetMonitor,
+    signal: Monitor<SignalStat>,
+}
+
+impl StatefulWidget for WirelessMonitor {
+    fn update(&mut self) -> Result<()> {
+        StatefulWidget::update(&mut self.net)?;
+        StatefulWidget::update(&mut self.signal)
+    }
+    fn render_widget<B: Backend>(&self, f: &mut Frame<B>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(area);
+
+        StatefulWidget::render_widget(&self.net, f, chunks[0]);
+        StatefulWidget::render_widget(&self.signal, f, chunks[1]);
+    }
+    fn handle_key(&mut self, key: Key) {
+        StatefulWidget::handle_key(&mut self.net, key);
+    }
+}
+
+impl WirelessMonitor {
+    pub fn new(name: &str, view: NetView) -> Result<WirelessMonitor> {
+        Ok(WirelessMonitor {
+            signal: Monitor::<SignalStat>::new(name)?,
+            net: NetMonitor::new(IfaceFilter::with_pattern(name), view)?,
+        })
+    }
+
+    /// Returns true if `name` is a wireless interface that is up
+    pub fn is_wireless(name: &str) -> bool {
+        Wireless::read(SYSFS, name).is_ok()
+    }
+
+    pub fn graph_loop(name: &str, view: NetView) -> Result<()> {
+        let mut monitor = WirelessMonitor::new(name, view)?;
+        single_widget_loop(&mut monitor, Config::new(TICK_RATE))
+    }
+}
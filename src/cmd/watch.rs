@@ -1,10 +1,11 @@
 use super::WatchOpts;
 use crate::{
     cli::RsysCli,
-    cmd::common::SystemInfo,
+    cmd::common::{InfoSections, SystemInfo},
+    linux::{rapl::RaplMeter, SYSFS},
     util::{print, PrintFormat},
 };
-use rsys::Result;
+use anyhow::Result;
 use std::{
     thread,
     time::{Duration, Instant},
@@ -18,27 +19,37 @@ impl RsysCli {
             Duration::from_secs(u64::MAX)
         };
         let interval: u128 = if let Some(i) = opts.interval { i as u128 } else { 1000 };
+        let mut rapl = if opts.power {
+            Some(RaplMeter::new(SYSFS)?)
+        } else if opts.all {
+            RaplMeter::new(SYSFS).ok()
+        } else {
+            None
+        };
         let loop_start = Instant::now();
         loop {
             let print_start = Instant::now();
+            let power = rapl.as_mut().map(|r| r.sample()).transpose()?.flatten();
             print(
                 SystemInfo::new(
                     &self.system,
-                    false,
-                    true,
-                    false,
-                    true,
-                    false,
-                    false,
-                    opts.cpu,
-                    opts.memory,
-                    opts.network,
-                    opts.storage,
-                    false,
-                    opts.all,
-                    opts.stats,
-                    false,
-                )?,
+                    InfoSections {
+                        hostname: true,
+                        uptime: true,
+                        load: true,
+                        pressure: true,
+                        cpu: opts.cpu,
+                        memory: opts.memory,
+                        net: opts.network,
+                        storage: opts.storage,
+                        all: opts.all,
+                        stats: opts.stats,
+                        ..InfoSections::default()
+                    },
+                )?
+                .with_power(power)
+                .with_conntrack_protocols(opts.conntrack_protocols)
+                .with_iface_filter(&opts.iface_filter),
                 PrintFormat::Json,
                 opts.pretty,
             )?;
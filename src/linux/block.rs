@@ -0,0 +1,513 @@
+use super::{read_parse, read_trimmed};
+use crate::util::conv_b;
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Formatter},
+    fs,
+    path::{Path, PathBuf},
+};
+
+const CLASS_BLOCK_DIR: &str = "class/block";
+const DISK_BY_UUID_DIR: &str = "/dev/disk/by-uuid";
+const DISK_BY_LABEL_DIR: &str = "/dev/disk/by-label";
+const UDEV_DATA_DIR: &str = "/run/udev/data";
+const MOUNTS_FILE: &str = "/proc/mounts";
+/// Unit of `size` and sector counters in `stat` of block devices. The kernel always
+/// reports them in 512 byte sectors regardless of `logical_block_size` of the device.
+pub const SECTOR_SIZE: u64 = 512;
+/// Guards against cycles in holders which should never happen but would recurse forever
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// A block device with everything stacked on top of it like partitions,
+/// device mappers or md arrays. Devices built from several others, like a
+/// raid1 array, appear under each of their members.
+pub struct BlockNode {
+    pub name: String,
+    /// Kind of the device like `disk`, `part`, `dm`, `crypt`, `lvm`, `loop`, `rom` or md raid level
+    pub kind: String,
+    /// Major and minor number like `8:0`
+    pub dev: String,
+    /// Size in bytes
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fstype: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mountpoint: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<BlockNode>,
+}
+
+impl BlockNode {
+    fn read(dir: &Path, ids: &DeviceIds, depth: usize) -> Result<BlockNode> {
+        let name = file_name(dir).ok_or_else(|| anyhow!("Invalid block device path `{}`", dir.display()))?;
+        let dev = read_trimmed(dir.join("dev"))?;
+        let mount = ids.mounts.get(&name);
+
+        let mut node = BlockNode {
+            kind: device_kind(dir, &name),
+            size: read_parse::<u64, _>(dir.join("size")).unwrap_or_default() * SECTOR_SIZE,
+            fstype: mount.map(|(_, fstype)| fstype.clone()).or_else(|| udev_fstype(&dev)),
+            label: ids.labels.get(&name).cloned(),
+            uuid: ids.uuids.get(&name).cloned(),
+            mountpoint: mount.map(|(mountpoint, _)| mountpoint.clone()),
+            children: Vec::new(),
+            name,
+            dev,
+        };
+
+        if depth < MAX_DEPTH {
+            let mut children = partitions(dir, &node.name);
+            children.extend(dir_entries(&dir.join("holders")));
+            children.sort();
+            node.children = children
+                .iter()
+                .filter_map(|child| BlockNode::read(child, ids, depth + 1).ok())
+                .collect();
+        }
+
+        Ok(node)
+    }
+
+    /// Returns this node and all of its descendants in depth first order together with
+    /// a prefix drawing the branches of the tree like `lsblk` does.
+    pub fn flatten(&self) -> Vec<(String, &BlockNode)> {
+        let mut nodes = vec![(String::new(), self)];
+        self.flatten_children("", &mut nodes);
+        nodes
+    }
+
+    fn flatten_children<'a>(&'a self, indent: &str, nodes: &mut Vec<(String, &'a BlockNode)>) {
+        let count = self.children.len();
+        for (i, child) in self.children.iter().enumerate() {
+            let last = i + 1 == count;
+            nodes.push((format!("{}{}", indent, if last { "└─" } else { "├─" }), child));
+            child.flatten_children(&format!("{}{}", indent, if last { "  " } else { "│ " }), nodes);
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Topology of all block devices starting from the ones not built on top of other devices
+pub struct BlockTree(pub Vec<BlockNode>);
+
+impl BlockTree {
+    /// Reads block device topology from sysfs mounted at `sysfs`, usually `/sys`.
+    /// Devices with zero size, like unused loop devices, are skipped.
+    pub fn read<P: AsRef<Path>>(sysfs: P) -> Result<BlockTree> {
+        let dir = sysfs.as_ref().join(CLASS_BLOCK_DIR);
+        if !dir.is_dir() {
+            return Err(anyhow!("Directory `{}` doesn't exist", dir.display()));
+        }
+        let ids = DeviceIds::read();
+
+        let mut roots = dir_entries(&dir)
+            .into_iter()
+            .filter(|p| !p.join("partition").exists() && dir_entries(&p.join("slaves")).is_empty())
+            .collect::<Vec<PathBuf>>();
+        roots.sort();
+
+        Ok(BlockTree(
+            roots
+                .iter()
+                .filter_map(|p| BlockNode::read(p, &ids, 0).ok())
+                .filter(|n| n.size > 0)
+                .collect(),
+        ))
+    }
+}
+
+impl fmt::Display for BlockTree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_CLEAN);
+        table.add_row(row![c => "name", "dev", "type", "size", "fstype", "label", "uuid", "mountpoint",]);
+        let opt = |v: &Option<String>| v.clone().unwrap_or_default();
+        for root in &self.0 {
+            for (prefix, node) in root.flatten() {
+                table.add_row(row![
+                    format!("{}{}", prefix, node.name),
+                    node.dev,
+                    node.kind,
+                    r -> conv_b(node.size),
+                    opt(&node.fstype),
+                    opt(&node.label),
+                    opt(&node.uuid),
+                    opt(&node.mountpoint),
+                ]);
+            }
+        }
+        write!(f, " STORAGE TREE:\n{}", table)
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Basic information about any block device including partitions and
+/// devices rsys has no dedicated type for like NVMe namespaces
+pub struct BlockDevice {
+    pub name: String,
+    /// Kind of the device like `disk`, `part`, `dm`, `crypt`, `lvm`, `loop`, `rom` or md raid level
+    pub kind: String,
+    /// Major and minor number like `8:0`
+    pub dev: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Disk containing this partition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub read_only: bool,
+    pub removable: bool,
+    #[serde(flatten)]
+    pub attributes: BlockAttributes,
+}
+
+impl BlockDevice {
+    /// Reads block device `name` from sysfs mounted at `sysfs`, usually `/sys`
+    pub fn read<P: AsRef<Path>>(sysfs: P, name: &str) -> Result<BlockDevice> {
+        let dir = sysfs.as_ref().join(CLASS_BLOCK_DIR).join(name);
+        if !dir.exists() {
+            return Err(anyhow!("Block device `{}` doesn't exist", name));
+        }
+        let parent = if dir.join("partition").exists() {
+            // Partitions are nested in the directory of their disk
+            fs::canonicalize(&dir).ok().and_then(|p| p.parent().and_then(file_name))
+        } else {
+            None
+        };
+
+        Ok(BlockDevice {
+            name: name.to_string(),
+            kind: device_kind(&dir, name),
+            dev: read_trimmed(dir.join("dev"))?,
+            size: read_parse::<u64, _>(dir.join("size")).unwrap_or_default() * SECTOR_SIZE,
+            parent,
+            model: read_trimmed(dir.join("device/model")).ok(),
+            read_only: read_parse::<u8, _>(dir.join("ro")).unwrap_or_default() != 0,
+            removable: read_parse::<u8, _>(dir.join("removable")).unwrap_or_default() != 0,
+            attributes: BlockAttributes::read(sysfs, name),
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Request queue and hardware attributes of a block device. Partitions share
+/// them with their disk. Attributes missing in sysfs are None.
+pub struct BlockAttributes {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotational: Option<bool>,
+    /// Active I/O scheduler like `mq-deadline` or `none`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduler: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logical_block_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub physical_block_size: Option<u64>,
+    /// Whether the device supports discard (TRIM)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discard: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nr_requests: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_ahead_kb: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub firmware: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial: Option<String>,
+}
+
+impl BlockAttributes {
+    /// Reads attributes of block device `name` from sysfs mounted at `sysfs`, usually `/sys`
+    pub fn read<P: AsRef<Path>>(sysfs: P, name: &str) -> BlockAttributes {
+        let mut dir = sysfs.as_ref().join(CLASS_BLOCK_DIR).join(name);
+        if dir.join("partition").exists() {
+            if let Some(disk) = fs::canonicalize(&dir)
+                .ok()
+                .and_then(|p| p.parent().map(Path::to_path_buf))
+            {
+                dir = disk;
+            }
+        }
+        let queue = dir.join("queue");
+        let non_empty = |s: String| if s.is_empty() { None } else { Some(s) };
+
+        BlockAttributes {
+            rotational: read_parse::<u8, _>(queue.join("rotational")).map(|r| r != 0),
+            // The active scheduler is the one in brackets like `[mq-deadline] kyber none`
+            scheduler: read_trimmed(queue.join("scheduler")).ok().and_then(|s| {
+                s.split_whitespace()
+                    .find(|s| s.starts_with('['))
+                    .map(|s| s.trim_matches(|c| c == '[' || c == ']').to_string())
+                    .or_else(|| non_empty(s))
+            }),
+            logical_block_size: read_parse(queue.join("logical_block_size")),
+            physical_block_size: read_parse(queue.join("physical_block_size")),
+            discard: read_parse::<u64, _>(queue.join("discard_max_bytes")).map(|d| d > 0),
+            nr_requests: read_parse(queue.join("nr_requests")),
+            read_ahead_kb: read_parse(queue.join("read_ahead_kb")),
+            // NVMe controllers expose `firmware_rev`, SCSI devices `rev`
+            firmware: read_trimmed(dir.join("device/firmware_rev"))
+                .or_else(|_| read_trimmed(dir.join("device/rev")))
+                .ok()
+                .and_then(non_empty),
+            serial: read_trimmed(dir.join("device/serial"))
+                .or_else(|_| read_trimmed(dir.join("serial")))
+                .ok()
+                .and_then(non_empty),
+        }
+    }
+
+    /// Adds rows with available attributes to a key value table
+    pub(crate) fn add_rows(&self, table: &mut Table) {
+        let mut kv = |k: &str, v: Option<String>| {
+            if let Some(v) = v {
+                table.add_row(row![k, l -> v]);
+            }
+        };
+        kv("rotational:", self.rotational.map(|r| r.to_string()));
+        kv("scheduler:", self.scheduler.clone());
+        kv("logical block size:", self.logical_block_size.map(|b| b.to_string()));
+        kv("physical block size:", self.physical_block_size.map(|b| b.to_string()));
+        kv("discard:", self.discard.map(|d| d.to_string()));
+        kv("nr requests:", self.nr_requests.map(|n| n.to_string()));
+        kv("read ahead:", self.read_ahead_kb.map(|r| format!("{} KiB", r)));
+        kv("firmware:", self.firmware.clone());
+        kv("serial:", self.serial.clone());
+    }
+}
+
+/// Returns attributes of all block devices except partitions keyed by device name
+pub fn block_attributes<P: AsRef<Path>>(sysfs: P) -> BTreeMap<String, BlockAttributes> {
+    let sysfs = sysfs.as_ref();
+    dir_entries(&sysfs.join(CLASS_BLOCK_DIR))
+        .iter()
+        .filter(|p| !p.join("partition").exists())
+        .filter_map(|p| file_name(p))
+        .map(|name| {
+            let attributes = BlockAttributes::read(sysfs, &name);
+            (name, attributes)
+        })
+        .collect()
+}
+
+impl fmt::Display for BlockAttributes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+        self.add_rows(&mut table);
+        write!(f, "{}", table)
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Flat list of all block devices sorted by name
+pub struct BlockDevices(pub Vec<BlockDevice>);
+
+impl BlockDevices {
+    /// Reads all block devices from sysfs mounted at `sysfs`, usually `/sys`
+    pub fn read<P: AsRef<Path>>(sysfs: P) -> Result<BlockDevices> {
+        let sysfs = sysfs.as_ref();
+        let dir = sysfs.join(CLASS_BLOCK_DIR);
+        if !dir.is_dir() {
+            return Err(anyhow!("Directory `{}` doesn't exist", dir.display()));
+        }
+        let mut names = dir_entries(&dir)
+            .iter()
+            .filter_map(|p| file_name(p))
+            .collect::<Vec<String>>();
+        names.sort();
+
+        Ok(BlockDevices(
+            names.iter().filter_map(|n| BlockDevice::read(sysfs, n).ok()).collect(),
+        ))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&BlockDevice> {
+        self.0.iter().find(|d| d.name == name)
+    }
+}
+
+impl fmt::Display for BlockDevice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+        table.add_row(row!["name:", l -> self.name]);
+        table.add_row(row!["type:", l -> self.kind]);
+        table.add_row(row!["dev:", l -> self.dev]);
+        table.add_row(row!["size:", l -> conv_b(self.size)]);
+        if let Some(parent) = &self.parent {
+            table.add_row(row!["parent:", l -> parent]);
+        }
+        if let Some(model) = &self.model {
+            table.add_row(row!["model:", l -> model]);
+        }
+        table.add_row(row!["read only:", l -> self.read_only]);
+        table.add_row(row!["removable:", l -> self.removable]);
+        self.attributes.add_rows(&mut table);
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for BlockDevices {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP);
+        table.add_row(row![c => "name", "type", "dev", "size", "parent", "model", "ro", "rm",]);
+        for d in &self.0 {
+            table.add_row(row![
+                d.name,
+                d.kind,
+                d.dev,
+                r -> conv_b(d.size),
+                d.parent.clone().unwrap_or_default(),
+                d.model.clone().unwrap_or_default(),
+                c -> d.read_only as u8,
+                c -> d.removable as u8,
+            ]);
+        }
+        write!(f, " BLOCK DEVICES:\n{}", table)
+    }
+}
+
+#[derive(Debug, Default)]
+// Identifiers of block devices not available in sysfs keyed by device name
+struct DeviceIds {
+    labels: HashMap<String, String>,
+    uuids: HashMap<String, String>,
+    /// Mountpoint and filesystem type
+    mounts: HashMap<String, (String, String)>,
+}
+
+impl DeviceIds {
+    fn read() -> DeviceIds {
+        DeviceIds {
+            labels: disk_links(Path::new(DISK_BY_LABEL_DIR)),
+            uuids: disk_links(Path::new(DISK_BY_UUID_DIR)),
+            mounts: mounted_devices(),
+        }
+    }
+}
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name().map(|n| n.to_string_lossy().to_string())
+}
+
+fn dir_entries(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+        .unwrap_or_default()
+}
+
+fn partitions(dir: &Path, name: &str) -> Vec<PathBuf> {
+    dir_entries(dir)
+        .into_iter()
+        .filter(|p| file_name(p).map(|n| n.starts_with(name)).unwrap_or(false) && p.join("partition").exists())
+        .collect()
+}
+
+fn device_kind(dir: &Path, name: &str) -> String {
+    if dir.join("partition").exists() {
+        return "part".to_string();
+    }
+    if dir.join("dm").is_dir() {
+        // Device mapper targets are recognizable by the prefix of their uuid
+        let uuid = read_trimmed(dir.join("dm/uuid")).unwrap_or_default();
+        return if uuid.starts_with("CRYPT-") {
+            "crypt"
+        } else if uuid.starts_with("LVM-") {
+            "lvm"
+        } else {
+            "dm"
+        }
+        .to_string();
+    }
+    if let Ok(level) = read_trimmed(dir.join("md/level")) {
+        return level;
+    }
+    if name.starts_with("loop") {
+        "loop".to_string()
+    } else if name.starts_with("sr") {
+        "rom".to_string()
+    } else {
+        "disk".to_string()
+    }
+}
+
+// Resolves symlinks like `/dev/disk/by-uuid/<uuid> -> ../../sda1` to a map of device name to link name
+fn disk_links(dir: &Path) -> HashMap<String, String> {
+    dir_entries(dir)
+        .into_iter()
+        .filter_map(|link| {
+            let target = fs::canonicalize(&link).ok()?;
+            Some((file_name(&target)?, unescape(&file_name(&link)?)))
+        })
+        .collect()
+}
+
+// Returns map of device name to its mountpoint and filesystem type. Only the first
+// mountpoint of a device is kept.
+fn mounted_devices() -> HashMap<String, (String, String)> {
+    let content = fs::read_to_string(MOUNTS_FILE).unwrap_or_default();
+    let mut mounts = HashMap::new();
+    for line in content.lines() {
+        let mut elems = line.split_whitespace();
+        if let (Some(source), Some(target), Some(fstype)) = (elems.next(), elems.next(), elems.next()) {
+            if !source.starts_with("/dev/") {
+                continue;
+            }
+            // Sources like `/dev/mapper/root` are symlinks to the actual `/dev/dm-N` device
+            let device = fs::canonicalize(source).unwrap_or_else(|_| PathBuf::from(source));
+            if let Some(name) = file_name(&device) {
+                mounts
+                    .entry(name)
+                    .or_insert_with(|| (unescape(target), fstype.to_string()));
+            }
+        }
+    }
+    mounts
+}
+
+// Filesystem type of unmounted devices is only known to udev which stores
+// the result of probing in its database
+fn udev_fstype(dev: &str) -> Option<String> {
+    let content = fs::read_to_string(Path::new(UDEV_DATA_DIR).join(format!("b{}", dev))).ok()?;
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("E:ID_FS_TYPE="))
+        .filter(|fstype| !fstype.is_empty())
+        .map(str::to_string)
+}
+
+// Decodes escape sequences used by the kernel in /proc/mounts like `\040`
+// and by udev in /dev/disk links like `\x20`
+fn unescape(s: &str) -> String {
+    let mut out = Vec::with_capacity(s.len());
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            let (digits, radix) = if bytes.get(i + 1) == Some(&b'x') {
+                (s.get(i + 2..i + 4), 16)
+            } else {
+                (s.get(i + 1..i + 4), 8)
+            };
+            if let Some(byte) = digits.and_then(|d| u8::from_str_radix(d, radix).ok()) {
+                out.push(byte);
+                // Both `\xHH` and `\OOO` are 4 bytes long
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
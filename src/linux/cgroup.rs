@@ -0,0 +1,308 @@
+use super::{parse_keyed, pressure::Pressure, read_parse, read_trimmed};
+use crate::util::{conv_b, conv_p, conv_t};
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Formatter},
+    fs,
+    path::{Path, PathBuf},
+};
+
+const UNLIMITED: &str = "max";
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Contents of `cpu.stat`. All times are in microseconds.
+pub struct CgroupCpu {
+    pub usage_usec: u64,
+    pub user_usec: u64,
+    pub system_usec: u64,
+    pub nr_periods: u64,
+    pub nr_throttled: u64,
+    pub throttled_usec: u64,
+}
+
+impl CgroupCpu {
+    fn parse(content: &str) -> CgroupCpu {
+        let mut cpu = CgroupCpu::default();
+        for (key, value) in parse_keyed(content) {
+            match key {
+                "usage_usec" => cpu.usage_usec = value,
+                "user_usec" => cpu.user_usec = value,
+                "system_usec" => cpu.system_usec = value,
+                "nr_periods" => cpu.nr_periods = value,
+                "nr_throttled" => cpu.nr_throttled = value,
+                "throttled_usec" => cpu.throttled_usec = value,
+                _ => {}
+            }
+        }
+        cpu
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Single device line of `io.stat`
+pub struct CgroupIo {
+    /// Device numbers as `major:minor`
+    pub device: String,
+    pub rbytes: u64,
+    pub wbytes: u64,
+    pub rios: u64,
+    pub wios: u64,
+    pub dbytes: u64,
+    pub dios: u64,
+}
+
+impl CgroupIo {
+    fn parse(line: &str) -> Option<CgroupIo> {
+        let mut elems = line.split_whitespace();
+        let mut io = CgroupIo {
+            device: elems.next()?.to_string(),
+            ..Default::default()
+        };
+        for field in elems {
+            let mut kv = field.split('=');
+            let key = kv.next()?;
+            let value = kv.next()?.parse::<u64>().ok()?;
+            match key {
+                "rbytes" => io.rbytes = value,
+                "wbytes" => io.wbytes = value,
+                "rios" => io.rios = value,
+                "wios" => io.wios = value,
+                "dbytes" => io.dbytes = value,
+                "dios" => io.dios = value,
+                _ => {}
+            }
+        }
+        Some(io)
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// A single cgroup v2 node with its usage, limits and child groups.
+/// Values of controllers that are not enabled for this group are missing.
+pub struct Cgroup {
+    /// Path relative to the cgroup root, `/` being the root itself
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<CgroupCpu>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_current: Option<u64>,
+    /// None if memory is not limited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_max: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub io: Vec<CgroupIo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pids_current: Option<u64>,
+    /// None if number of pids is not limited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pids_max: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_pressure: Option<Pressure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_pressure: Option<Pressure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub io_pressure: Option<Pressure>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Cgroup>,
+}
+
+impl Cgroup {
+    /// Reads cgroup at `path` relative to `root` together with all of its descendants.
+    pub fn read<P: AsRef<Path>>(root: P, path: &str) -> Result<Cgroup> {
+        let root = root.as_ref();
+        let path = normalize(path);
+        let dir = root.join(path.trim_start_matches('/'));
+        if !dir.is_dir() {
+            return Err(anyhow!("Cgroup `{}` not found in `{}`", path, root.display()));
+        }
+
+        Cgroup::read_tree(&dir, path)
+    }
+
+    fn read_tree(dir: &Path, path: String) -> Result<Cgroup> {
+        let mut cgroup = Cgroup::read_single(dir, path);
+
+        let mut children = fs::read_dir(dir)
+            .map_err(|e| anyhow!("Failed to list cgroup `{}` - {}", dir.display(), e))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|p| p.is_dir())
+            .collect::<Vec<PathBuf>>();
+        children.sort();
+
+        for child in children {
+            let name = child
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let path = if cgroup.path == "/" {
+                format!("/{}", name)
+            } else {
+                format!("{}/{}", cgroup.path, name)
+            };
+            // Children may be removed while the tree is read, for example when a container stops
+            if let Ok(child) = Cgroup::read_tree(&child, path) {
+                cgroup.children.push(child);
+            }
+        }
+
+        Ok(cgroup)
+    }
+
+    fn read_single(dir: &Path, path: String) -> Cgroup {
+        Cgroup {
+            path,
+            cpu: read_trimmed(dir.join("cpu.stat")).ok().map(|s| CgroupCpu::parse(&s)),
+            memory_current: read_parse(dir.join("memory.current")),
+            memory_max: read_limit(dir.join("memory.max")),
+            io: read_trimmed(dir.join("io.stat"))
+                .map(|s| s.lines().filter_map(CgroupIo::parse).collect())
+                .unwrap_or_default(),
+            pids_current: read_parse(dir.join("pids.current")),
+            pids_max: read_limit(dir.join("pids.max")),
+            cpu_pressure: Pressure::from_file(dir.join("cpu.pressure")).ok(),
+            memory_pressure: Pressure::from_file(dir.join("memory.pressure")).ok(),
+            io_pressure: Pressure::from_file(dir.join("io.pressure")).ok(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Last component of the path or `/` for the root group
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').find(|s| !s.is_empty()).unwrap_or("/")
+    }
+
+    /// Sum of read and written bytes across all devices
+    pub fn io_bytes(&self) -> (u64, u64) {
+        self.io.iter().fold((0, 0), |(r, w), io| (r + io.rbytes, w + io.wbytes))
+    }
+
+    /// Returns this group and all of its descendants in depth first order together
+    /// with their depth relative to this group.
+    pub fn flatten(&self) -> Vec<(usize, &Cgroup)> {
+        let mut groups = Vec::new();
+        self.flatten_into(0, &mut groups);
+        groups
+    }
+
+    fn flatten_into<'a>(&'a self, depth: usize, groups: &mut Vec<(usize, &'a Cgroup)>) {
+        groups.push((depth, self));
+        self.children.iter().for_each(|c| c.flatten_into(depth + 1, groups));
+    }
+}
+
+impl fmt::Display for Cgroup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP);
+        table.add_row(row![
+            c =>
+            "cgroup",
+            "cpu time",
+            "throttled",
+            "memory",
+            "memory max",
+            "io read",
+            "io write",
+            "pids",
+            "cpu psi",
+            "mem psi",
+            "io psi",
+        ]);
+
+        for (depth, cg) in self.flatten() {
+            let name = if depth == 0 {
+                cg.path.clone()
+            } else {
+                format!("{}{}", "  ".repeat(depth), cg.name())
+            };
+            let (rbytes, wbytes) = cg.io_bytes();
+            table.add_row(row![
+                name,
+                r -> opt_str(cg.cpu.as_ref().map(|c| conv_t(c.usage_usec as f64 / 1_000_000.))),
+                r -> opt_str(cg.cpu.as_ref().map(|c| conv_t(c.throttled_usec as f64 / 1_000_000.))),
+                r -> opt_str(cg.memory_current.map(conv_b)),
+                r -> limit_str(cg.memory_max.map(conv_b)),
+                r -> conv_b(rbytes),
+                r -> conv_b(wbytes),
+                r -> format!("{} / {}", opt_str(cg.pids_current), limit_str(cg.pids_max)),
+                r -> opt_str(cg.cpu_pressure.map(|p| conv_p(p.some.avg10))),
+                r -> opt_str(cg.memory_pressure.map(|p| conv_p(p.some.avg10))),
+                r -> opt_str(cg.io_pressure.map(|p| conv_p(p.some.avg10))),
+            ]);
+        }
+
+        write!(
+            f,
+            " CGROUPS: (psi - share of time some tasks stalled over last 10s)\n{}",
+            table
+        )
+    }
+}
+
+/// Makes sure the path starts with exactly one `/` and has no trailing one
+fn normalize(path: &str) -> String {
+    let trimmed = path.trim_matches('/');
+    format!("/{}", trimmed)
+}
+
+/// Reads a limit file like `memory.max` returning None if it is set to `max`
+fn read_limit<P: AsRef<Path>>(path: P) -> Option<u64> {
+    let value = read_trimmed(path).ok()?;
+    if value == UNLIMITED {
+        return None;
+    }
+    value.parse::<u64>().ok()
+}
+
+fn opt_str<T: ToString>(val: Option<T>) -> String {
+    val.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+fn limit_str<T: ToString>(val: Option<T>) -> String {
+    val.map(|v| v.to_string()).unwrap_or_else(|| UNLIMITED.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_cgroup_tree_from_fixture() {
+        let root = std::env::temp_dir().join(format!("rsys-cli-cgroup-{}", std::process::id()));
+        let child = root.join("system.slice");
+        fs::create_dir_all(&child).unwrap();
+        let write = |path: PathBuf, content: &str| fs::write(path, content).unwrap();
+        write(
+            root.join("cpu.stat"),
+            "usage_usec 1500\nuser_usec 1000\nsystem_usec 500\n",
+        );
+        write(root.join("memory.current"), "4096\n");
+        write(root.join("memory.max"), "max\n");
+        write(root.join("pids.max"), "100\n");
+        write(child.join("memory.current"), "1024\n");
+        write(
+            child.join("io.stat"),
+            "8:0 rbytes=10 wbytes=20 rios=1 wios=2 dbytes=0 dios=0\n",
+        );
+
+        let cgroup = Cgroup::read(&root, "/");
+        fs::remove_dir_all(&root).unwrap();
+        let cgroup = cgroup.unwrap();
+
+        assert_eq!(cgroup.path, "/");
+        assert_eq!(cgroup.cpu.map(|cpu| cpu.usage_usec), Some(1500));
+        assert_eq!(cgroup.memory_current, Some(4096));
+        assert_eq!(cgroup.memory_max, None);
+        assert_eq!(cgroup.pids_max, Some(100));
+        assert_eq!(cgroup.children.len(), 1);
+
+        let child = &cgroup.children[0];
+        assert_eq!(child.path, "/system.slice");
+        assert_eq!(child.memory_current, Some(1024));
+        assert_eq!(child.io_bytes(), (10, 20));
+        assert!(child.cpu.is_none());
+    }
+}
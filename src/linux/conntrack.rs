@@ -0,0 +1,133 @@
+use super::read_parse;
+use crate::util::conv_p;
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Formatter},
+    fs,
+    path::Path,
+};
+
+const NETFILTER_DIR: &str = "/proc/sys/net/netfilter";
+const CONNTRACK_FILE: &str = "/proc/net/nf_conntrack";
+const CONNTRACK_STAT_FILE: &str = "/proc/net/stat/nf_conntrack";
+/// Usage of the table in percent above which new connections are at risk of being dropped
+pub const WARN_PERCENT: f64 = 90.;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Usage of the netfilter connection tracking table. Once the table is full
+/// packets of new connections are dropped.
+pub struct Conntrack {
+    pub count: u64,
+    /// Value of `nf_conntrack_max`
+    pub max: u64,
+    /// Packets dropped because an entry couldn't be allocated, summed over all cpus
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drops: Option<u64>,
+    /// Entries evicted to make room for new ones in a full table, summed over all cpus
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub early_drops: Option<u64>,
+    /// Entries of each layer 4 protocol like `tcp` or `udp`. Only available if the kernel
+    /// was built with `CONFIG_NF_CONNTRACK_PROCFS` and we have permissions to read it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocols: Option<BTreeMap<String, u64>>,
+}
+
+impl Conntrack {
+    /// Reads number of entries and limit of the table without per protocol counts
+    /// which require reading the whole table
+    pub fn read() -> Result<Conntrack> {
+        let dir = Path::new(NETFILTER_DIR);
+        let count = read_parse::<u64, _>(dir.join("nf_conntrack_count"))
+            .ok_or_else(|| anyhow!("Connection tracking is not enabled, `nf_conntrack` module is not loaded"))?;
+        let max = read_parse::<u64, _>(dir.join("nf_conntrack_max"))
+            .ok_or_else(|| anyhow!("Failed to read `{}`", dir.join("nf_conntrack_max").display()))?;
+
+        let stats = fs::read_to_string(CONNTRACK_STAT_FILE)
+            .map(|content| Conntrack::parse_stat(&content))
+            .unwrap_or_default();
+        Ok(Conntrack {
+            count,
+            max,
+            drops: stats.get("drop").copied(),
+            early_drops: stats.get("early_drop").copied(),
+            protocols: None,
+        })
+    }
+
+    /// Adds number of entries of each protocol
+    pub fn with_protocols(mut self) -> Self {
+        self.protocols = fs::read_to_string(CONNTRACK_FILE)
+            .ok()
+            .map(|content| Conntrack::parse_protocols(&content));
+        self
+    }
+
+    /// Parses /proc/net/stat/nf_conntrack where the first line has names of counters and
+    /// every other line has their values in hex for a single cpu. Returns sums of values.
+    pub fn parse_stat(content: &str) -> HashMap<String, u64> {
+        let mut lines = content.lines();
+        let names = match lines.next() {
+            Some(names) => names.split_whitespace().collect::<Vec<&str>>(),
+            None => return HashMap::new(),
+        };
+        let mut sums = HashMap::new();
+        for line in lines {
+            for (name, value) in names.iter().zip(line.split_whitespace()) {
+                if let Ok(value) = u64::from_str_radix(value, 16) {
+                    *sums.entry(name.to_string()).or_insert(0) += value;
+                }
+            }
+        }
+        sums
+    }
+
+    /// Counts entries of /proc/net/nf_conntrack by layer 4 protocol, the third column of lines like:
+    /// `ipv4     2 tcp      6 431999 ESTABLISHED src=10.0.0.2 dst=10.0.0.1 ...`
+    pub fn parse_protocols(content: &str) -> BTreeMap<String, u64> {
+        let mut protocols = BTreeMap::new();
+        for proto in content.lines().filter_map(|line| line.split_whitespace().nth(2)) {
+            *protocols.entry(proto.to_string()).or_insert(0) += 1;
+        }
+        protocols
+    }
+
+    pub fn usage_percent(&self) -> f64 {
+        if self.max == 0 {
+            return 0.;
+        }
+        100. * self.count as f64 / self.max as f64
+    }
+
+    pub fn is_nearly_full(&self) -> bool {
+        self.usage_percent() >= WARN_PERCENT
+    }
+}
+
+impl fmt::Display for Conntrack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+        let opt = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string());
+        table.add_row(row!["entries:", r -> self.count]);
+        table.add_row(row!["max:", r -> self.max]);
+        table.add_row(row![
+            "usage:",
+            r -> if self.is_nearly_full() {
+                format!("{} (nearly full)", conv_p(self.usage_percent()))
+            } else {
+                conv_p(self.usage_percent())
+            }
+        ]);
+        table.add_row(row!["drops:", r -> opt(self.drops)]);
+        table.add_row(row!["early drops:", r -> opt(self.early_drops)]);
+        if let Some(protocols) = &self.protocols {
+            for (proto, count) in protocols {
+                table.add_row(row![format!("{}:", proto), r -> count]);
+            }
+        }
+        write!(f, " CONNTRACK:\n{}", table)
+    }
+}
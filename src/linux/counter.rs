@@ -0,0 +1,110 @@
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+/// Change of a counter between two consecutive samples
+pub enum CounterDelta {
+    /// Counter increased by this amount
+    Delta(u64),
+    /// There is no previous sample to compare against
+    #[default]
+    Missing,
+    /// Counter went backwards without wrapping around, for example because an
+    /// interface was recreated or a driver reloaded
+    Reset,
+}
+impl CounterDelta {
+    /// Returns the delta or None if it couldn't be determined
+    pub fn value(self) -> Option<u64> {
+        match self {
+            CounterDelta::Delta(delta) => Some(delta),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+/// A monotonically increasing kernel counter like transferred bytes or cpu time.
+/// Keeps the previous sample so that deltas between samples can be calculated
+/// even if the counter wraps around or is reset.
+pub struct Counter {
+    last: Option<u64>,
+    /// Last valid value of the counter if known. The sample after it is zero.
+    max: Option<u64>,
+}
+impl Counter {
+    /// Returns a counter with `initial` as the previous sample
+    pub fn new(initial: u64) -> Self {
+        Self {
+            last: Some(initial),
+            max: None,
+        }
+    }
+
+    /// Sets the last valid value of the counter after which it wraps around to zero. Only
+    /// counters known to wrap, like ones kept by the kernel in 32 bits with `u32::MAX`,
+    /// should set it.
+    pub fn with_max(mut self, max: u64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Records a new sample returning its delta to the previous one
+    pub fn update(&mut self, value: u64) -> CounterDelta {
+        let delta = match self.last {
+            None => CounterDelta::Missing,
+            Some(last) if value >= last => CounterDelta::Delta(value - last),
+            Some(last) => self.wrapped(last, value),
+        };
+        self.last = Some(value);
+        delta
+    }
+
+    // Counter went backwards so it either wrapped around or was reset. Without a known
+    // wrap boundary a drop can't be told apart from a reset, so it's always treated as one.
+    fn wrapped(&self, last: u64, value: u64) -> CounterDelta {
+        match self.max {
+            // `max` is inclusive so stepping from it to zero counts as one
+            Some(max) if last <= max => CounterDelta::Delta(max - last + value + 1),
+            _ => CounterDelta::Reset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_sample_is_missing() {
+        let mut counter = Counter::default();
+        assert_eq!(counter.update(10), CounterDelta::Missing);
+        assert_eq!(counter.update(15), CounterDelta::Delta(5));
+    }
+
+    #[test]
+    fn increase_is_delta() {
+        let mut counter = Counter::new(100);
+        assert_eq!(counter.update(100), CounterDelta::Delta(0));
+        assert_eq!(counter.update(250), CounterDelta::Delta(150));
+    }
+
+    #[test]
+    fn drop_without_max_is_reset() {
+        // A 3 GB counter of a recreated interface must not look like a 32 bit wraparound
+        let mut counter = Counter::new(3_000_000_000);
+        assert_eq!(counter.update(1000), CounterDelta::Reset);
+        assert_eq!(counter.update(1500), CounterDelta::Delta(500));
+    }
+
+    #[test]
+    fn drop_with_max_wraps_around() {
+        let mut counter = Counter::new(u32::MAX as u64 - 9).with_max(u32::MAX as u64);
+        assert_eq!(counter.update(5), CounterDelta::Delta(15));
+        let mut counter = Counter::new(u32::MAX as u64).with_max(u32::MAX as u64);
+        assert_eq!(counter.update(0), CounterDelta::Delta(1));
+    }
+
+    #[test]
+    fn last_above_max_is_reset() {
+        let mut counter = Counter::new(2000).with_max(1000);
+        assert_eq!(counter.update(10), CounterDelta::Reset);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::util::{conv_b, conv_p};
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use rsys::linux::misc::MountPoints;
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::CString,
+    fmt::{self, Formatter},
+    mem::MaybeUninit,
+};
+
+/// Filesystem types that don't store any data and only clutter the output
+const PSEUDO_FILESYSTEMS: &[&str] = &[
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "devtmpfs",
+    "efivarfs",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "nsfs",
+    "proc",
+    "pstore",
+    "rpc_pipefs",
+    "securityfs",
+    "selinuxfs",
+    "sysfs",
+    "tracefs",
+];
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Space and inode usage of a mounted filesystem. Sizes are in bytes.
+pub struct FsUsage {
+    pub device: String,
+    pub mountpoint: String,
+    pub fstype: String,
+    pub size: u64,
+    pub used: u64,
+    /// Space available to unprivileged users
+    pub available: u64,
+    pub inodes: u64,
+    pub inodes_used: u64,
+    pub inodes_free: u64,
+}
+
+impl FsUsage {
+    /// Queries usage of filesystem mounted at `mountpoint` with statvfs
+    pub fn read(device: &str, mountpoint: &str, fstype: &str) -> Result<FsUsage> {
+        let path = CString::new(mountpoint).map_err(|e| anyhow!("Invalid mountpoint `{}` - {}", mountpoint, e))?;
+        let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+        // Safety: path is a valid null terminated string and stat is only read
+        // after statvfs reports success
+        let stat = unsafe {
+            if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+                return Err(anyhow!(
+                    "Failed to stat filesystem at `{}` - {}",
+                    mountpoint,
+                    std::io::Error::last_os_error()
+                ));
+            }
+            stat.assume_init()
+        };
+
+        let fragment = stat.f_frsize as u64;
+        Ok(FsUsage {
+            device: device.to_string(),
+            mountpoint: mountpoint.to_string(),
+            fstype: fstype.to_string(),
+            size: stat.f_blocks as u64 * fragment,
+            used: (stat.f_blocks as u64).saturating_sub(stat.f_bfree as u64) * fragment,
+            available: stat.f_bavail as u64 * fragment,
+            inodes: stat.f_files as u64,
+            inodes_used: (stat.f_files as u64).saturating_sub(stat.f_ffree as u64),
+            inodes_free: stat.f_ffree as u64,
+        })
+    }
+
+    /// Rereads usage of this filesystem
+    pub fn update(&mut self) -> Result<()> {
+        *self = FsUsage::read(&self.device, &self.mountpoint, &self.fstype)?;
+        Ok(())
+    }
+
+    /// Returns used space in percent the same way `df` does, that is relative
+    /// to space available to unprivileged users
+    pub fn used_percent(&self) -> f64 {
+        let usable = self.used + self.available;
+        if usable == 0 {
+            return 0.;
+        }
+        100. * self.used as f64 / usable as f64
+    }
+
+    /// Returns used inodes in percent or None if filesystem has no inode limit
+    pub fn inodes_used_percent(&self) -> Option<f64> {
+        if self.inodes == 0 {
+            return None;
+        }
+        Some(100. * self.inodes_used as f64 / self.inodes as f64)
+    }
+
+    /// Whether this is a virtual filesystem like `proc` or one without any blocks
+    pub fn is_pseudo(&self) -> bool {
+        self.size == 0 || PSEUDO_FILESYSTEMS.contains(&self.fstype.as_str())
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct FsUsages(pub Vec<FsUsage>);
+
+impl FsUsages {
+    /// Queries usage of all mounted filesystems sorting them by fullness. Mountpoints
+    /// that can't be queried, for example due to permissions, are skipped.
+    pub fn from_mounts(mounts: &MountPoints, include_pseudo: bool) -> FsUsages {
+        let mut usages = mounts
+            .0
+            .iter()
+            .filter_map(|m| FsUsage::read(&m.volume, &m.path, &m.voltype).ok())
+            .filter(|u| include_pseudo || !u.is_pseudo())
+            .collect::<Vec<FsUsage>>();
+        usages.sort_by(|u1, u2| {
+            u2.used_percent()
+                .partial_cmp(&u1.used_percent())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        FsUsages(usages)
+    }
+}
+
+impl fmt::Display for FsUsages {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP);
+        table.add_row(row![c => "device", "mountpoint", "type", "size", "used", "avail", "use", "inodes",]);
+        for u in &self.0 {
+            table.add_row(row![
+                u.device,
+                u.mountpoint,
+                u.fstype,
+                r -> conv_b(u.size),
+                r -> conv_b(u.used),
+                r -> conv_b(u.available),
+                r -> conv_p(u.used_percent()),
+                r -> u.inodes_used_percent().map(conv_p).unwrap_or_default(),
+            ]);
+        }
+        write!(f, " FILESYSTEMS:\n{}", table)
+    }
+}
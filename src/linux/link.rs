@@ -0,0 +1,198 @@
+use super::{read_parse, read_trimmed};
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Formatter},
+    fs,
+    path::Path,
+};
+
+pub(crate) const CLASS_NET_DIR: &str = "class/net";
+// ARPHRD_LOOPBACK from `include/uapi/linux/if_arp.h`
+const ARPHRD_LOOPBACK: u32 = 772;
+// IFF_TAP flag of `tun_flags`
+const IFF_TAP: u32 = 0x0002;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InterfaceKind {
+    Physical,
+    Wireless,
+    Loopback,
+    Bridge,
+    Bond,
+    Vlan,
+    Veth,
+    Tun,
+    Tap,
+    /// Any other virtual interface like wireguard or dummy
+    Virtual,
+}
+impl fmt::Display for InterfaceKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            InterfaceKind::Physical => "physical",
+            InterfaceKind::Wireless => "wireless",
+            InterfaceKind::Loopback => "loopback",
+            InterfaceKind::Bridge => "bridge",
+            InterfaceKind::Bond => "bond",
+            InterfaceKind::Vlan => "vlan",
+            InterfaceKind::Veth => "veth",
+            InterfaceKind::Tun => "tun",
+            InterfaceKind::Tap => "tap",
+            InterfaceKind::Virtual => "virtual",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Link state and hardware details of a network interface from /sys/class/net/<iface>.
+/// Carrier, duplex and speed are unavailable while the interface is down.
+pub struct LinkDetails {
+    pub kind: InterfaceKind,
+    /// RFC 2863 operational state like `up`, `down`, `dormant` or `unknown`
+    pub operstate: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub carrier: Option<bool>,
+    /// Number of times carrier was gained or lost, increases with every link flap
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub carrier_changes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplex: Option<String>,
+    /// Link speed in Mb/s
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver: Option<String>,
+    /// PCI address like `0000:03:00.0` of PCI devices
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pci_address: Option<String>,
+}
+
+impl LinkDetails {
+    /// Reads details of interface `name` from sysfs mounted at `sysfs`, usually `/sys`
+    pub fn read<P: AsRef<Path>>(sysfs: P, name: &str) -> Result<LinkDetails> {
+        let dir = sysfs.as_ref().join(CLASS_NET_DIR).join(name);
+        if !dir.exists() {
+            return Err(anyhow!("Interface `{}` not found in `{}`", name, dir.display()));
+        }
+        let device = dir.join("device");
+
+        Ok(LinkDetails {
+            kind: interface_kind(&dir),
+            operstate: read_trimmed(dir.join("operstate"))?,
+            carrier: read_parse::<u8, _>(dir.join("carrier")).map(|c| c == 1),
+            carrier_changes: read_parse(dir.join("carrier_changes")),
+            duplex: read_trimmed(dir.join("duplex")).ok().filter(|d| d != "unknown"),
+            // Speed is -1 if unknown
+            speed: read_parse::<i64, _>(dir.join("speed"))
+                .filter(|s| *s > 0)
+                .map(|s| s as u64),
+            driver: link_name(&device.join("driver")),
+            pci_address: match link_name(&device.join("subsystem")).as_deref() {
+                Some("pci") => link_name(&device),
+                _ => None,
+            },
+        })
+    }
+
+    /// Short description of the link like `up 1000Mb/s full`
+    pub fn link_str(&self) -> String {
+        let mut s = self.operstate.clone();
+        if let Some(speed) = self.speed {
+            s.push_str(&format!(" {}Mb/s", speed));
+        }
+        if let Some(duplex) = &self.duplex {
+            s.push(' ');
+            s.push_str(duplex);
+        }
+        s
+    }
+
+    pub(crate) fn add_rows(&self, table: &mut Table) {
+        let opt = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+        table.add_row(row!["type:", l -> self.kind]);
+        table.add_row(row!["operstate:", l -> self.operstate]);
+        table.add_row(row!["carrier:", l -> opt(self.carrier.map(|c| if c { "yes" } else { "no" }.to_string()))]);
+        table.add_row(row!["carrier changes:", l -> opt(self.carrier_changes.map(|c| c.to_string()))]);
+        table.add_row(row!["duplex:", l -> opt(self.duplex.clone())]);
+        table.add_row(row!["link speed:", l -> opt(self.speed.map(|s| format!("{} Mb/s", s)))]);
+        table.add_row(row!["driver:", l -> opt(self.driver.clone())]);
+        table.add_row(row!["pci address:", l -> opt(self.pci_address.clone())]);
+    }
+}
+
+/// Returns link details of all interfaces keyed by name
+pub fn link_details<P: AsRef<Path>>(sysfs: P) -> BTreeMap<String, LinkDetails> {
+    let sysfs = sysfs.as_ref();
+    fs::read_dir(sysfs.join(CLASS_NET_DIR))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| {
+                    let name = e.file_name().to_string_lossy().to_string();
+                    LinkDetails::read(sysfs, &name).ok().map(|details| (name, details))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Guesses kind of the interface from attributes that only some kinds have
+pub(crate) fn interface_kind(dir: &Path) -> InterfaceKind {
+    if read_parse::<u32, _>(dir.join("type")) == Some(ARPHRD_LOOPBACK) {
+        return InterfaceKind::Loopback;
+    }
+    let devtype = read_trimmed(dir.join("uevent")).ok().and_then(|uevent| {
+        uevent
+            .lines()
+            .find_map(|l| l.strip_prefix("DEVTYPE=").map(|t| t.to_string()))
+    });
+    match devtype.as_deref() {
+        Some("bridge") => return InterfaceKind::Bridge,
+        Some("bond") => return InterfaceKind::Bond,
+        Some("vlan") => return InterfaceKind::Vlan,
+        Some("wlan") => return InterfaceKind::Wireless,
+        Some(_) => return InterfaceKind::Virtual,
+        None => {}
+    }
+    if dir.join("bridge").exists() {
+        InterfaceKind::Bridge
+    } else if dir.join("bonding").exists() {
+        InterfaceKind::Bond
+    } else if let Ok(flags) = read_trimmed(dir.join("tun_flags")) {
+        let flags = u32::from_str_radix(flags.trim_start_matches("0x"), 16).unwrap_or_default();
+        if flags & IFF_TAP != 0 {
+            InterfaceKind::Tap
+        } else {
+            InterfaceKind::Tun
+        }
+    } else if dir.join("device").exists() {
+        InterfaceKind::Physical
+    } else if read_parse::<u32, _>(dir.join("iflink")) != read_parse::<u32, _>(dir.join("ifindex")) {
+        // Veth pairs link to their peer, vlans were already matched by devtype
+        InterfaceKind::Veth
+    } else {
+        InterfaceKind::Virtual
+    }
+}
+
+// Returns name of the file a symlink points to
+fn link_name(path: &Path) -> Option<String> {
+    fs::read_link(path)
+        .ok()?
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+}
+
+impl fmt::Display for LinkDetails {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+        self.add_rows(&mut table);
+        write!(f, "{}", table)
+    }
+}
@@ -0,0 +1,96 @@
+use super::read_trimmed;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Formatter};
+
+const LOADAVG_PATH: &str = "/proc/loadavg";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// System load averages and task counts from `/proc/loadavg`
+pub struct LoadAvg {
+    /// Load average over last minute
+    pub one: f64,
+    /// Load average over last 5 minutes
+    pub five: f64,
+    /// Load average over last 15 minutes
+    pub fifteen: f64,
+    /// Number of currently runnable tasks
+    pub running: u64,
+    /// Number of all tasks in the system
+    pub total: u64,
+    /// PID of the most recently created process
+    pub last_pid: u64,
+}
+
+impl LoadAvg {
+    /// Parses content of `/proc/loadavg`, for example `0.52 0.48 0.40 2/1234 56789`
+    pub fn parse(content: &str) -> Result<LoadAvg> {
+        let invalid = || anyhow!("Invalid loadavg format `{}`", content);
+        let elems = content.split_whitespace().collect::<Vec<&str>>();
+        if elems.len() < 5 {
+            return Err(invalid());
+        }
+        let mut tasks = elems[3].split('/');
+
+        Ok(LoadAvg {
+            one: elems[0].parse().map_err(|_| invalid())?,
+            five: elems[1].parse().map_err(|_| invalid())?,
+            fifteen: elems[2].parse().map_err(|_| invalid())?,
+            running: tasks.next().and_then(|t| t.parse().ok()).ok_or_else(invalid)?,
+            total: tasks.next().and_then(|t| t.parse().ok()).ok_or_else(invalid)?,
+            last_pid: elems[4].parse().map_err(|_| invalid())?,
+        })
+    }
+
+    pub fn read() -> Result<LoadAvg> {
+        LoadAvg::parse(&read_trimmed(LOADAVG_PATH)?)
+    }
+
+    /// Returns all three averages joined like `0.52 0.48 0.40`
+    pub fn averages_str(&self) -> String {
+        format!("{:.2} {:.2} {:.2}", self.one, self.five, self.fifteen)
+    }
+
+    /// Returns running and total tasks joined like `2/1234`
+    pub fn tasks_str(&self) -> String {
+        format!("{}/{}", self.running, self.total)
+    }
+}
+
+impl fmt::Display for LoadAvg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "load: {}, tasks: {}, last pid: {}",
+            self.averages_str(),
+            self.tasks_str(),
+            self.last_pid
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_averages_and_tasks() {
+        assert_eq!(
+            LoadAvg::parse("0.52 0.48 0.40 2/1234 56789").unwrap(),
+            LoadAvg {
+                one: 0.52,
+                five: 0.48,
+                fifteen: 0.40,
+                running: 2,
+                total: 1234,
+                last_pid: 56789,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_content() {
+        assert!(LoadAvg::parse("0.52 0.48 0.40").is_err());
+        assert!(LoadAvg::parse("0.52 0.48 0.40 1234 56789").is_err());
+    }
+}
@@ -0,0 +1,345 @@
+use super::{read_parse, read_trimmed};
+use crate::util::{conv_fbs, conv_p};
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Formatter},
+    fs,
+    path::Path,
+};
+
+const MDSTAT_FILE: &str = "/proc/mdstat";
+const SYS_BLOCK_DIR: &str = "block";
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// A device that is part of an md array
+pub struct MdMember {
+    pub name: String,
+    /// Position of the device in the array
+    pub role: Option<u32>,
+    /// State from `md/dev-*/state` like `in_sync`, `faulty`, `spare` or `write_mostly`.
+    /// Falls back to flags from /proc/mdstat if sysfs is unavailable.
+    pub state: String,
+}
+
+impl MdMember {
+    pub fn is_faulty(&self) -> bool {
+        self.state.contains("faulty")
+    }
+
+    pub fn is_spare(&self) -> bool {
+        self.state.contains("spare")
+    }
+
+    // Parses a member like `sda1[0]` or `sdb1[1](F)` from /proc/mdstat
+    fn parse(s: &str) -> Option<MdMember> {
+        let (name, rest) = s.split_at(s.find('[')?);
+        let end = rest.find(']')?;
+        let role = rest[1..end].parse::<u32>().ok();
+        let state = match &rest[end + 1..] {
+            "(F)" => "faulty",
+            "(S)" => "spare",
+            "(W)" => "in_sync,write_mostly",
+            "(R)" => "replacement",
+            _ => "in_sync",
+        };
+        Some(MdMember {
+            name: name.to_string(),
+            role,
+            state: state.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Progress of a resync, recovery, check, repair or reshape of an md array
+pub struct MdSync {
+    /// Running action like `resync`, `recovery`, `check`, `repair` or `reshape`
+    pub action: String,
+    /// Progress in percent
+    pub progress: f64,
+    /// Speed in KiB/s
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<u64>,
+    /// Estimated time to finish in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eta: Option<u64>,
+}
+
+impl MdSync {
+    // Parses a progress line like:
+    // `[=>...................]  recovery =  8.3% (87424/1047552) finish=0.1min speed=87424K/sec`
+    fn parse(line: &str) -> Option<MdSync> {
+        // Skip the progress bar as it consists of `=` too
+        let line = line.trim();
+        let line = if line.starts_with('[') {
+            &line[line.find(']')? + 1..]
+        } else {
+            line
+        };
+        let (action, rest) = line.split_at(line.find('=')?);
+        let action = action.split_whitespace().last()?.to_string();
+        let mut elems = rest[1..].split_whitespace();
+        let progress = elems.next()?.trim_end_matches('%').parse::<f64>().ok()?;
+        let mut sync = MdSync {
+            action,
+            progress,
+            speed: None,
+            eta: None,
+        };
+        for elem in elems {
+            if let Some(finish) = elem.strip_prefix("finish=") {
+                sync.eta = finish
+                    .trim_end_matches("min")
+                    .parse::<f64>()
+                    .ok()
+                    .map(|min| (min * 60.) as u64);
+            } else if let Some(speed) = elem.strip_prefix("speed=") {
+                sync.speed = speed.trim_end_matches("K/sec").parse::<u64>().ok();
+            }
+        }
+        Some(sync)
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Health of a software RAID array
+pub struct MdArray {
+    pub name: String,
+    pub level: String,
+    /// State from `md/array_state` like `clean`, `active` or `readonly`, or
+    /// `active`/`inactive` from /proc/mdstat
+    pub state: String,
+    /// Number of devices the array should consist of
+    pub raid_disks: u32,
+    /// Number of devices missing from the array
+    pub degraded: u32,
+    /// Number of members marked as faulty
+    pub failed: u32,
+    pub members: Vec<MdMember>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sync: Option<MdSync>,
+}
+
+impl MdArray {
+    pub fn is_degraded(&self) -> bool {
+        self.degraded > 0 || self.failed > 0
+    }
+
+    /// Short description of the array health like `degraded (1/2 active, 1 failed)` or `ok`
+    pub fn health(&self) -> String {
+        if self.is_degraded() {
+            format!(
+                "degraded ({}/{} active, {} failed)",
+                self.raid_disks.saturating_sub(self.degraded),
+                self.raid_disks,
+                self.failed
+            )
+        } else {
+            "ok".to_string()
+        }
+    }
+
+    /// Description of a running sync like `recovery 8.3% 89.52MB/s ~1m`
+    pub fn sync_str(&self) -> Option<String> {
+        self.sync.as_ref().map(|s| {
+            let mut out = format!("{} {}", s.action, conv_p(s.progress));
+            if let Some(speed) = s.speed {
+                out.push_str(&format!(" {}", conv_fbs(speed as f64 * 1024.)));
+            }
+            match s.eta {
+                Some(eta) if eta < 60 => out.push_str(&format!(" ~{}s", eta)),
+                Some(eta) => out.push_str(&format!(" ~{}m", eta / 60)),
+                None => {}
+            }
+            out
+        })
+    }
+
+    // Parses a single array block of /proc/mdstat. The first line is the header
+    // like `md0 : active raid1 sdb1[1] sda1[0]`, the following lines are indented.
+    fn parse(lines: &[&str]) -> Option<MdArray> {
+        let mut header = lines.first()?.split_whitespace();
+        let name = header.next()?.to_string();
+        header.next()?; // `:`
+        let state = header.next()?.to_string();
+        let mut array = MdArray {
+            name,
+            state,
+            ..Default::default()
+        };
+        for elem in header {
+            if elem.contains('[') {
+                array.members.extend(MdMember::parse(elem));
+            } else if !elem.starts_with('(') {
+                // Skips flags like `(auto-read-only)` that follow the state
+                array.level = elem.to_string();
+            }
+        }
+
+        for line in &lines[1..] {
+            if line.contains("blocks") {
+                // `[2/1]` is number of disks and number of working disks
+                if let Some(counts) = line.split_whitespace().find(|e| e.starts_with('[') && e.contains('/')) {
+                    let mut counts = counts.trim_matches(|c| c == '[' || c == ']').split('/');
+                    let total = counts.next().and_then(|c| c.parse::<u32>().ok()).unwrap_or_default();
+                    let active = counts.next().and_then(|c| c.parse::<u32>().ok()).unwrap_or(total);
+                    array.raid_disks = total;
+                    array.degraded = total.saturating_sub(active);
+                }
+            } else if line.contains('%') {
+                array.sync = MdSync::parse(line);
+            }
+        }
+        array.failed = array.members.iter().filter(|m| m.is_faulty()).count() as u32;
+
+        Some(array)
+    }
+
+    // Overrides values from /proc/mdstat with more detailed ones from `md/` directory in sysfs
+    fn update_from_sysfs(&mut self, sysfs: &Path) {
+        let dir = sysfs.join(SYS_BLOCK_DIR).join(&self.name).join("md");
+        if !dir.is_dir() {
+            return;
+        }
+        if let Ok(state) = read_trimmed(dir.join("array_state")) {
+            self.state = state;
+        }
+        if let Some(degraded) = read_parse(dir.join("degraded")) {
+            self.degraded = degraded;
+        }
+        if let Some(raid_disks) = read_parse(dir.join("raid_disks")) {
+            self.raid_disks = raid_disks;
+        }
+        for member in &mut self.members {
+            let member_dir = dir.join(format!("dev-{}", member.name));
+            if let Ok(state) = read_trimmed(member_dir.join("state")) {
+                member.state = state;
+            }
+            if let Ok(slot) = read_trimmed(member_dir.join("slot")) {
+                member.role = slot.parse::<u32>().ok();
+            }
+        }
+        self.failed = self.members.iter().filter(|m| m.is_faulty()).count() as u32;
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// All md arrays of the system
+pub struct MdArrays(pub Vec<MdArray>);
+
+impl MdArrays {
+    /// Parses /proc/mdstat completing it with attributes from sysfs mounted at `sysfs`, usually `/sys`
+    pub fn read<P: AsRef<Path>>(sysfs: P) -> Result<MdArrays> {
+        let content =
+            fs::read_to_string(MDSTAT_FILE).map_err(|e| anyhow!("Failed to read `{}` - {}", MDSTAT_FILE, e))?;
+        let mut arrays = MdArrays::parse(&content);
+        arrays.0.iter_mut().for_each(|a| a.update_from_sysfs(sysfs.as_ref()));
+        Ok(arrays)
+    }
+
+    pub fn parse(content: &str) -> MdArrays {
+        let mut arrays = Vec::new();
+        let mut block: Vec<&str> = Vec::new();
+        for line in content.lines() {
+            if line.starts_with("md") {
+                arrays.extend(MdArray::parse(&block));
+                block.clear();
+                block.push(line);
+            } else if line.starts_with(char::is_whitespace) && !block.is_empty() {
+                block.push(line);
+            }
+        }
+        arrays.extend(MdArray::parse(&block));
+        MdArrays(arrays)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MdArray> {
+        self.0.iter().find(|a| a.name == name)
+    }
+
+    pub fn degraded(&self) -> impl Iterator<Item = &MdArray> {
+        self.0.iter().filter(|a| a.is_degraded())
+    }
+}
+
+impl fmt::Display for MdArray {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+        table.add_row(row!["name:", l -> self.name]);
+        table.add_row(row!["level:", l -> self.level]);
+        table.add_row(row!["state:", l -> self.state]);
+        table.add_row(row!["health:", l -> self.health()]);
+        if let Some(sync) = self.sync_str() {
+            table.add_row(row!["sync:", l -> sync]);
+        }
+        for member in &self.members {
+            let role = member.role.map(|r| r.to_string()).unwrap_or_else(|| "-".to_string());
+            table.add_row(row![format!("member {}:", role), l -> format!("{} ({})", member.name, member.state)]);
+        }
+        write!(f, "{}", table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MDSTAT: &str = "Personalities : [raid1] [raid6] [raid5] [raid4]
+md1 : active raid1 sdb2[1] sda2[0]
+      1047552 blocks super 1.2 [2/2] [UU]
+      [=>...................]  resync =  8.3% (87424/1047552) finish=0.1min speed=87424K/sec
+
+md0 : active raid1 sdc1[2] sdb1[1](F) sda1[0]
+      1047552 blocks super 1.2 [2/1] [U_]
+      [==>..................]  recovery = 12.6% (132096/1047552) finish=2.5min speed=6048K/sec
+
+md2 : active (auto-read-only) raid5 sdd1[3](S) sde1[1] sdf1[0]
+      2093056 blocks super 1.2 level 5, 512k chunk, algorithm 2 [3/3] [UUU]
+
+unused devices: <none>
+";
+
+    #[test]
+    fn parses_resync() {
+        let arrays = MdArrays::parse(MDSTAT);
+        assert_eq!(arrays.0.len(), 3);
+        let md1 = arrays.get("md1").unwrap();
+        assert_eq!(md1.level, "raid1");
+        assert_eq!(md1.state, "active");
+        assert_eq!(md1.raid_disks, 2);
+        assert!(!md1.is_degraded());
+        let sync = md1.sync.as_ref().unwrap();
+        assert_eq!(sync.action, "resync");
+        assert_eq!(sync.progress, 8.3);
+        assert_eq!(sync.speed, Some(87424));
+        assert_eq!(sync.eta, Some(6));
+    }
+
+    #[test]
+    fn parses_degraded_array_in_recovery() {
+        let arrays = MdArrays::parse(MDSTAT);
+        let md0 = arrays.get("md0").unwrap();
+        assert_eq!(md0.members.len(), 3);
+        assert!(md0.members.iter().any(|m| m.name == "sdb1" && m.is_faulty()));
+        assert_eq!(md0.degraded, 1);
+        assert_eq!(md0.failed, 1);
+        assert_eq!(md0.health(), "degraded (1/2 active, 1 failed)");
+        let sync = md0.sync.as_ref().unwrap();
+        assert_eq!(sync.action, "recovery");
+        assert_eq!(sync.eta, Some(150));
+    }
+
+    #[test]
+    fn skips_flags_and_keeps_spares() {
+        let arrays = MdArrays::parse(MDSTAT);
+        let md2 = arrays.get("md2").unwrap();
+        assert_eq!(md2.level, "raid5");
+        assert_eq!(md2.raid_disks, 3);
+        assert!(md2.members.iter().any(|m| m.name == "sdd1" && m.is_spare()));
+        assert!(!md2.is_degraded());
+        assert!(md2.sync.is_none());
+    }
+}
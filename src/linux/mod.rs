@@ -0,0 +1,54 @@
+//! Readers for kernel interfaces in procfs and sysfs that rsys doesn't expose.
+pub mod block;
+pub mod cgroup;
+pub mod conntrack;
+pub mod counter;
+pub mod fs;
+pub mod link;
+pub mod loadavg;
+pub mod md;
+pub mod neighbor;
+pub mod netstat;
+pub mod ports;
+pub mod power_supply;
+pub mod pressure;
+pub mod rapl;
+pub mod route;
+pub mod sensors;
+pub mod socket;
+pub mod topology;
+pub mod wireless;
+
+use anyhow::{anyhow, Result};
+use std::{path::Path, str::FromStr};
+
+/// Default mount point of sysfs used when the root isn't overridden
+pub const SYSFS: &str = "/sys";
+
+/// Reads the whole file at path returning its content with surrounding whitespace removed.
+pub fn read_trimmed<P: AsRef<Path>>(path: P) -> Result<String> {
+    let path = path.as_ref();
+    std::fs::read_to_string(path)
+        .map(|s| s.trim().to_string())
+        .map_err(|e| anyhow!("Failed to read `{}` - {}", path.display(), e))
+}
+
+/// Reads and parses a single value file like most of sysfs attributes. Returns None
+/// if the file is missing or its content is not a valid T.
+pub fn read_parse<T: FromStr, P: AsRef<Path>>(path: P) -> Option<T> {
+    read_trimmed(path).ok()?.parse::<T>().ok()
+}
+
+/// Parses a flat keyed file where each line is a `key value` pair like `cpu.stat`.
+/// Lines that fail to parse are skipped.
+pub fn parse_keyed(content: &str) -> Vec<(&str, u64)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut elems = line.split_whitespace();
+            let key = elems.next()?;
+            let value = elems.next()?.parse::<u64>().ok()?;
+            Some((key, value))
+        })
+        .collect()
+}
@@ -0,0 +1,134 @@
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Formatter},
+    fs,
+    net::IpAddr,
+};
+
+const ARP_FILE: &str = "/proc/net/arp";
+// ARP entry flags from `include/uapi/linux/if_arp.h`
+const ATF_COM: u32 = 0x02;
+const ATF_PERM: u32 = 0x04;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NeighborState {
+    /// Hardware address was resolved
+    Complete,
+    /// Resolution is in progress or failed
+    Incomplete,
+    /// Static entry added by hand
+    Permanent,
+}
+impl fmt::Display for NeighborState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = match self {
+            NeighborState::Complete => "complete",
+            NeighborState::Incomplete => "incomplete",
+            NeighborState::Permanent => "permanent",
+        };
+        write!(f, "{}", state)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Neighbor {
+    pub address: IpAddr,
+    /// Hardware address, missing until resolution completes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mac_address: Option<String>,
+    /// ARP hardware type, 1 for ethernet
+    pub hw_type: u16,
+    pub state: NeighborState,
+    pub iface: String,
+}
+
+impl Neighbor {
+    /// Parses a line of /proc/net/arp like:
+    /// `192.168.0.1  0x1  0x2  00:11:22:33:44:55  *  eth0`
+    pub fn parse(line: &str) -> Option<Neighbor> {
+        let elems = line.split_whitespace().collect::<Vec<&str>>();
+        if elems.len() < 6 {
+            return None;
+        }
+        let hex = |s: &str| u32::from_str_radix(s.trim_start_matches("0x"), 16).ok();
+        let flags = hex(elems[2])?;
+        Some(Neighbor {
+            address: elems[0].parse().ok()?,
+            mac_address: if flags & ATF_COM != 0 {
+                Some(elems[3].to_string())
+            } else {
+                None
+            },
+            hw_type: hex(elems[1])? as u16,
+            state: if flags & ATF_PERM != 0 {
+                NeighborState::Permanent
+            } else if flags & ATF_COM != 0 {
+                NeighborState::Complete
+            } else {
+                NeighborState::Incomplete
+            },
+            iface: elems[5].to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// IPv4 neighbors from the ARP cache. IPv6 neighbors are only available through netlink.
+pub struct Neighbors(pub Vec<Neighbor>);
+
+impl Neighbors {
+    pub fn read() -> Result<Neighbors> {
+        let content = fs::read_to_string(ARP_FILE).map_err(|e| anyhow!("Failed to read `{}` - {}", ARP_FILE, e))?;
+        Ok(Neighbors::parse(&content))
+    }
+
+    pub fn parse(content: &str) -> Neighbors {
+        Neighbors(content.lines().skip(1).filter_map(Neighbor::parse).collect())
+    }
+}
+
+impl fmt::Display for Neighbors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP);
+        table.add_row(row![c => "address", "mac", "state", "iface",]);
+        for neighbor in &self.0 {
+            table.add_row(row![
+                neighbor.address,
+                neighbor.mac_address.clone().unwrap_or_else(|| "-".to_string()),
+                neighbor.state,
+                neighbor.iface,
+            ]);
+        }
+        write!(f, " NEIGHBORS:\n{}", table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_arp_entries() {
+        let neighbors = Neighbors::parse(
+            "IP address       HW type     Flags       HW address            Mask     Device
+192.168.0.1      0x1         0x2         00:11:22:33:44:55     *        eth0
+192.168.0.7      0x1         0x0         00:00:00:00:00:00     *        eth0
+10.0.0.5         0x1         0x6         aa:bb:cc:dd:ee:ff     *        br0
+",
+        )
+        .0;
+        assert_eq!(neighbors.len(), 3);
+        assert_eq!(neighbors[0].address, "192.168.0.1".parse::<IpAddr>().unwrap());
+        assert_eq!(neighbors[0].mac_address.as_deref(), Some("00:11:22:33:44:55"));
+        assert_eq!(neighbors[0].state, NeighborState::Complete);
+        assert_eq!(neighbors[0].hw_type, 1);
+        assert_eq!(neighbors[1].state, NeighborState::Incomplete);
+        assert_eq!(neighbors[1].mac_address, None);
+        assert_eq!(neighbors[2].state, NeighborState::Permanent);
+        assert_eq!(neighbors[2].iface, "br0");
+    }
+}
@@ -0,0 +1,171 @@
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::{self, Formatter},
+    fs,
+};
+
+const SNMP_FILE: &str = "/proc/net/snmp";
+const NETSTAT_FILE: &str = "/proc/net/netstat";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Protocol counters since boot from /proc/net/snmp and /proc/net/netstat like `netstat -s`.
+/// Counters missing on the running kernel are zero.
+pub struct NetStat {
+    /// Sent TCP segments, used as a base for retransmit ratio
+    pub tcp_out_segs: u64,
+    pub tcp_retrans_segs: u64,
+    /// Established connections reset by either side
+    pub tcp_estab_resets: u64,
+    /// Sent segments with RST flag
+    pub tcp_out_rsts: u64,
+    /// Failed connection attempts
+    pub tcp_attempt_fails: u64,
+    /// Received segments with errors
+    pub tcp_in_errs: u64,
+    /// Times accept queue of a listening socket overflowed
+    pub tcp_listen_overflows: u64,
+    /// Connection requests dropped by listening sockets for any reason
+    pub tcp_listen_drops: u64,
+    pub udp_in_errors: u64,
+    /// Datagrams received on a port nobody listens on
+    pub udp_no_ports: u64,
+    pub udp_rcvbuf_errors: u64,
+    pub udp_sndbuf_errors: u64,
+    /// Received fragments that needed reassembly
+    pub ip_reasm_reqds: u64,
+    pub ip_reasm_fails: u64,
+    /// Datagrams that were successfully fragmented
+    pub ip_frag_oks: u64,
+    pub ip_frag_fails: u64,
+    /// Fragments created by fragmenting
+    pub ip_frag_creates: u64,
+}
+
+impl NetStat {
+    pub fn read() -> Result<NetStat> {
+        let snmp = fs::read_to_string(SNMP_FILE).map_err(|e| anyhow!("Failed to read `{}` - {}", SNMP_FILE, e))?;
+        let mut counters = NetStat::parse(&snmp);
+        // Extended counters like ListenOverflows live in a separate file
+        if let Ok(netstat) = fs::read_to_string(NETSTAT_FILE) {
+            counters.extend(NetStat::parse(&netstat));
+        }
+
+        let get = |group: &str, name: &str| {
+            counters
+                .get(&(group.to_string(), name.to_string()))
+                .copied()
+                .unwrap_or_default()
+        };
+        Ok(NetStat {
+            tcp_out_segs: get("Tcp", "OutSegs"),
+            tcp_retrans_segs: get("Tcp", "RetransSegs"),
+            tcp_estab_resets: get("Tcp", "EstabResets"),
+            tcp_out_rsts: get("Tcp", "OutRsts"),
+            tcp_attempt_fails: get("Tcp", "AttemptFails"),
+            tcp_in_errs: get("Tcp", "InErrs"),
+            tcp_listen_overflows: get("TcpExt", "ListenOverflows"),
+            tcp_listen_drops: get("TcpExt", "ListenDrops"),
+            udp_in_errors: get("Udp", "InErrors"),
+            udp_no_ports: get("Udp", "NoPorts"),
+            udp_rcvbuf_errors: get("Udp", "RcvbufErrors"),
+            udp_sndbuf_errors: get("Udp", "SndbufErrors"),
+            ip_reasm_reqds: get("Ip", "ReasmReqds"),
+            ip_reasm_fails: get("Ip", "ReasmFails"),
+            ip_frag_oks: get("Ip", "FragOKs"),
+            ip_frag_fails: get("Ip", "FragFails"),
+            ip_frag_creates: get("Ip", "FragCreates"),
+        })
+    }
+
+    /// Parses a file with pairs of lines where the first one has names of counters
+    /// and the second one their values like:
+    /// ```text
+    /// Tcp: RtoAlgorithm RtoMin RtoMax MaxConn ActiveOpens ...
+    /// Tcp: 1 200 120000 -1 1024 ...
+    /// ```
+    /// Returns values keyed by group and name. Negative values like `MaxConn` are skipped.
+    pub fn parse(content: &str) -> HashMap<(String, String), u64> {
+        let mut counters = HashMap::new();
+        let mut lines = content.lines();
+        while let (Some(names), Some(values)) = (lines.next(), lines.next()) {
+            let mut names = names.split_whitespace();
+            let group = match names.next() {
+                Some(group) => group.trim_end_matches(':'),
+                None => continue,
+            };
+            for (name, value) in names.zip(values.split_whitespace().skip(1)) {
+                if let Ok(value) = value.parse::<u64>() {
+                    counters.insert((group.to_string(), name.to_string()), value);
+                }
+            }
+        }
+        counters
+    }
+
+    /// Returns error and fragmentation counters with their display names
+    pub fn counters(&self) -> Vec<(&'static str, u64)> {
+        vec![
+            ("tcp retransmits", self.tcp_retrans_segs),
+            ("tcp estab resets", self.tcp_estab_resets),
+            ("tcp out resets", self.tcp_out_rsts),
+            ("tcp attempt fails", self.tcp_attempt_fails),
+            ("tcp in errors", self.tcp_in_errs),
+            ("tcp listen overflows", self.tcp_listen_overflows),
+            ("tcp listen drops", self.tcp_listen_drops),
+            ("udp in errors", self.udp_in_errors),
+            ("udp no ports", self.udp_no_ports),
+            ("udp rcvbuf errors", self.udp_rcvbuf_errors),
+            ("udp sndbuf errors", self.udp_sndbuf_errors),
+            ("ip reasm reqds", self.ip_reasm_reqds),
+            ("ip reasm fails", self.ip_reasm_fails),
+            ("ip frag oks", self.ip_frag_oks),
+            ("ip frag fails", self.ip_frag_fails),
+            ("ip frag creates", self.ip_frag_creates),
+        ]
+    }
+
+    /// Returns percentage of retransmitted TCP segments out of all sent
+    pub fn retrans_percent(&self) -> f64 {
+        if self.tcp_out_segs == 0 {
+            return 0.;
+        }
+        100. * self.tcp_retrans_segs as f64 / self.tcp_out_segs as f64
+    }
+}
+
+impl fmt::Display for NetStat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+        table.add_row(row!["tcp out segments:", r -> self.tcp_out_segs]);
+        for (name, value) in self.counters() {
+            table.add_row(row![format!("{}:", name), r -> value]);
+        }
+        write!(f, " NETSTAT:\n{}", table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_counters_by_group() {
+        let counters = NetStat::parse(
+            "Tcp: RtoAlgorithm RtoMin RtoMax MaxConn ActiveOpens OutSegs RetransSegs
+Tcp: 1 200 120000 -1 1024 5000 25
+Udp: InDatagrams NoPorts InErrors
+Udp: 100 3 0
+",
+        );
+        let get = |group: &str, name: &str| counters.get(&(group.to_string(), name.to_string())).copied();
+        assert_eq!(get("Tcp", "OutSegs"), Some(5000));
+        assert_eq!(get("Tcp", "RetransSegs"), Some(25));
+        assert_eq!(get("Tcp", "MaxConn"), None);
+        assert_eq!(get("Udp", "NoPorts"), Some(3));
+        assert_eq!(get("Udp", "OutSegs"), None);
+    }
+}
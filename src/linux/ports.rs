@@ -0,0 +1,163 @@
+use super::{
+    read_trimmed,
+    socket::{SocketProto, SocketState, Sockets},
+};
+use anyhow::Result;
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::{self, Formatter},
+    fs,
+    net::IpAddr,
+    path::Path,
+};
+
+const PROC_DIR: &str = "/proc";
+const PASSWD_FILE: &str = "/etc/passwd";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A listening TCP socket or a bound UDP socket together with the process owning it.
+/// Owner is unknown if the process belongs to another user and we lack permissions.
+pub struct ListeningPort {
+    pub proto: SocketProto,
+    pub address: IpAddr,
+    pub port: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+impl ListeningPort {
+    /// Short description like `tcp/8080`
+    pub fn short(&self) -> String {
+        format!("{}/{}", self.proto, self.port)
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ListeningPorts(pub Vec<ListeningPort>);
+
+impl ListeningPorts {
+    /// Reads listening TCP and bound UDP sockets and finds their owners by matching socket
+    /// inodes with file descriptors in /proc/<pid>/fd
+    pub fn read() -> Result<ListeningPorts> {
+        let sockets = Sockets::read()?;
+        let owners = socket_owners();
+        let users = users();
+
+        let mut ports = sockets
+            .0
+            .into_iter()
+            .filter(|s| match s.proto {
+                SocketProto::Tcp | SocketProto::Tcp6 => s.state == SocketState::Listen,
+                // Unconnected UDP sockets are reported as closed
+                SocketProto::Udp | SocketProto::Udp6 => s.state == SocketState::Close,
+                SocketProto::Unix => false,
+            })
+            .filter_map(|s| {
+                let local = s.local?;
+                let pid = owners.get(&s.inode).copied();
+                Some(ListeningPort {
+                    proto: s.proto,
+                    address: local.ip(),
+                    port: local.port(),
+                    pid,
+                    process: pid
+                        .and_then(|pid| read_trimmed(Path::new(PROC_DIR).join(pid.to_string()).join("comm")).ok()),
+                    user: s
+                        .uid
+                        .map(|uid| users.get(&uid).cloned().unwrap_or_else(|| uid.to_string())),
+                })
+            })
+            .collect::<Vec<ListeningPort>>();
+        ports.sort_by(|p1, p2| p1.port.cmp(&p2.port).then(p1.proto.cmp(&p2.proto)));
+
+        Ok(ListeningPorts(ports))
+    }
+
+    /// Keeps only sockets bound to `port`
+    pub fn filter(self, port: Option<u16>) -> ListeningPorts {
+        ListeningPorts(
+            self.0
+                .into_iter()
+                .filter(|p| port.map_or(true, |port| p.port == port))
+                .collect(),
+        )
+    }
+
+    /// Returns ports owned by process `pid`
+    pub fn of_pid(&self, pid: i32) -> impl Iterator<Item = &ListeningPort> {
+        self.0.iter().filter(move |p| p.pid == Some(pid))
+    }
+}
+
+// Maps inodes of sockets to pids of processes that have them open. Processes
+// whose fd directory can't be read are skipped.
+fn socket_owners() -> HashMap<u64, i32> {
+    let mut owners = HashMap::new();
+    let entries = match fs::read_dir(PROC_DIR) {
+        Ok(entries) => entries,
+        Err(_) => return owners,
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let pid = match entry.file_name().to_str().and_then(|name| name.parse::<i32>().ok()) {
+            Some(pid) => pid,
+            None => continue,
+        };
+        let fds = match fs::read_dir(entry.path().join("fd")) {
+            Ok(fds) => fds,
+            Err(_) => continue,
+        };
+        for fd in fds.filter_map(|e| e.ok()) {
+            // Links of sockets look like `socket:[12345]`
+            if let Some(inode) = fs::read_link(fd.path()).ok().and_then(|link| {
+                link.to_str()?
+                    .strip_prefix("socket:[")?
+                    .strip_suffix(']')?
+                    .parse::<u64>()
+                    .ok()
+            }) {
+                owners.entry(inode).or_insert(pid);
+            }
+        }
+    }
+    owners
+}
+
+// Maps uids to user names from /etc/passwd
+fn users() -> HashMap<u32, String> {
+    fs::read_to_string(PASSWD_FILE)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut elems = line.split(':');
+            let name = elems.next()?;
+            let uid = elems.nth(1)?.parse::<u32>().ok()?;
+            Some((uid, name.to_string()))
+        })
+        .collect()
+}
+
+impl fmt::Display for ListeningPorts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP);
+        table.add_row(row![c => "proto", "address", "port", "pid", "process", "user",]);
+        let opt = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+        for p in &self.0 {
+            table.add_row(row![
+                p.proto,
+                p.address,
+                r -> p.port,
+                r -> opt(p.pid.map(|pid| pid.to_string())),
+                opt(p.process.clone()),
+                opt(p.user.clone()),
+            ]);
+        }
+        write!(f, " LISTENING PORTS:\n{}", table)
+    }
+}
@@ -0,0 +1,225 @@
+use super::{read_parse, read_trimmed};
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Formatter},
+    fs,
+    path::{Path, PathBuf},
+};
+
+const POWER_SUPPLY_DIR: &str = "class/power_supply";
+const BATTERY_TYPE: &str = "Battery";
+const STATUS_CHARGING: &str = "Charging";
+const STATUS_DISCHARGING: &str = "Discharging";
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// A single entry of `/sys/class/power_supply` like an AC adapter or a battery.
+/// Charge values are in µAh, energy in µWh, power in µW, current in µA and
+/// voltage in µV as reported by the kernel. Attributes the driver doesn't
+/// provide are missing.
+pub struct PowerSupply {
+    pub name: String,
+    /// Type of the supply like `Mains`, `Battery` or `USB`
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub online: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Remaining capacity in percent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub charge_now: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub charge_full: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub charge_full_design: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub energy_now: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub energy_full: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub energy_full_design: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power_now: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_now: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voltage_now: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cycle_count: Option<u64>,
+    /// Estimated seconds until the battery is empty when discharging or full when charging
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_remaining: Option<u64>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl PowerSupply {
+    /// Reads power supply from its sysfs directory
+    pub fn read<P: AsRef<Path>>(dir: P) -> Result<PowerSupply> {
+        let dir = dir.as_ref();
+        let name = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| anyhow!("Invalid power supply path `{}`", dir.display()))?;
+        let mut supply = PowerSupply {
+            name,
+            kind: read_trimmed(dir.join("type"))?,
+            path: dir.to_path_buf(),
+            ..Default::default()
+        };
+        supply.update()?;
+        Ok(supply)
+    }
+
+    /// Rereads all dynamic attributes of this power supply
+    pub fn update(&mut self) -> Result<()> {
+        let dir = &self.path;
+        if !dir.exists() {
+            return Err(anyhow!("Power supply `{}` disappeared", self.name));
+        }
+        let attr = |name: &str| dir.join(name);
+
+        self.online = read_parse::<u8, _>(attr("online")).map(|o| o != 0);
+        self.status = read_trimmed(attr("status")).ok();
+        self.capacity = read_parse(attr("capacity"));
+        self.charge_now = read_parse(attr("charge_now"));
+        self.charge_full = read_parse(attr("charge_full"));
+        self.charge_full_design = read_parse(attr("charge_full_design"));
+        self.energy_now = read_parse(attr("energy_now"));
+        self.energy_full = read_parse(attr("energy_full"));
+        self.energy_full_design = read_parse(attr("energy_full_design"));
+        // Some drivers report negative values while discharging
+        self.power_now = read_parse::<i64, _>(attr("power_now")).map(i64::unsigned_abs);
+        self.current_now = read_parse::<i64, _>(attr("current_now")).map(i64::unsigned_abs);
+        self.voltage_now = read_parse(attr("voltage_now"));
+        self.health = read_trimmed(attr("health")).ok();
+        self.cycle_count = read_parse(attr("cycle_count"));
+        self.time_remaining = self.estimate_time_remaining();
+
+        Ok(())
+    }
+
+    pub fn is_battery(&self) -> bool {
+        self.kind == BATTERY_TYPE
+    }
+
+    /// Current power draw in watts, calculated from current and voltage if
+    /// the driver doesn't report power directly
+    pub fn power_watts(&self) -> Option<f64> {
+        if let Some(power) = self.power_now {
+            return Some(power as f64 / 1_000_000.);
+        }
+        match (self.current_now, self.voltage_now) {
+            (Some(current), Some(voltage)) => Some(current as f64 * voltage as f64 / 1e12),
+            _ => None,
+        }
+    }
+
+    fn estimate_time_remaining(&self) -> Option<u64> {
+        let status = self.status.as_deref()?;
+        let reported = match status {
+            STATUS_DISCHARGING => "time_to_empty_now",
+            STATUS_CHARGING => "time_to_full_now",
+            _ => return None,
+        };
+        if let Some(secs) = read_parse(self.path.join(reported)) {
+            return Some(secs);
+        }
+
+        let (now, full, rate) = if self.energy_now.is_some() {
+            (self.energy_now?, self.energy_full, self.power_now)
+        } else {
+            (self.charge_now?, self.charge_full, self.current_now)
+        };
+        let rate = rate.filter(|r| *r > 0)? as f64;
+        let left = if status == STATUS_DISCHARGING {
+            now
+        } else {
+            full?.saturating_sub(now)
+        };
+        Some((left as f64 / rate * 3600.) as u64)
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// All power supplies found in sysfs
+pub struct PowerSupplies(pub Vec<PowerSupply>);
+
+impl PowerSupplies {
+    /// Reads power supplies from sysfs mounted at `sysfs`, usually `/sys`
+    pub fn read<P: AsRef<Path>>(sysfs: P) -> Result<PowerSupplies> {
+        let dir = sysfs.as_ref().join(POWER_SUPPLY_DIR);
+        let mut paths = fs::read_dir(&dir)
+            .map_err(|e| anyhow!("Failed to read directory `{}` - {}", dir.display(), e))?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .collect::<Vec<PathBuf>>();
+        paths.sort();
+
+        Ok(PowerSupplies(
+            paths.iter().filter_map(|p| PowerSupply::read(p).ok()).collect(),
+        ))
+    }
+}
+
+impl fmt::Display for PowerSupplies {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP);
+        table.add_row(row![
+            c =>
+            "name",
+            "type",
+            "online",
+            "status",
+            "capacity",
+            "now",
+            "full",
+            "design",
+            "power",
+            "health",
+            "cycles",
+            "remaining",
+        ]);
+        for s in &self.0 {
+            let (now, full, design) = if s.energy_now.is_some() {
+                (
+                    s.energy_now.map(|e| format!("{:.2} Wh", e as f64 / 1e6)),
+                    s.energy_full.map(|e| format!("{:.2} Wh", e as f64 / 1e6)),
+                    s.energy_full_design.map(|e| format!("{:.2} Wh", e as f64 / 1e6)),
+                )
+            } else {
+                (
+                    s.charge_now.map(|c| format!("{:.0} mAh", c as f64 / 1e3)),
+                    s.charge_full.map(|c| format!("{:.0} mAh", c as f64 / 1e3)),
+                    s.charge_full_design.map(|c| format!("{:.0} mAh", c as f64 / 1e3)),
+                )
+            };
+            table.add_row(row![
+                s.name,
+                s.kind,
+                s.online.map(|o| if o { "yes" } else { "no" }).unwrap_or_default(),
+                s.status.clone().unwrap_or_default(),
+                r -> s.capacity.map(|c| format!("{}%", c)).unwrap_or_default(),
+                r -> now.unwrap_or_default(),
+                r -> full.unwrap_or_default(),
+                r -> design.unwrap_or_default(),
+                r -> s.power_watts().map(|p| format!("{:.2} W", p)).unwrap_or_default(),
+                s.health.clone().unwrap_or_default(),
+                r -> s.cycle_count.map(|c| c.to_string()).unwrap_or_default(),
+                r -> s.time_remaining.map(fmt_duration).unwrap_or_default(),
+            ]);
+        }
+        write!(f, " POWER SUPPLIES:\n{}", table)
+    }
+}
+
+/// Formats seconds like `2h 05m`
+pub fn fmt_duration(secs: u64) -> String {
+    format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+}
@@ -0,0 +1,208 @@
+use super::read_trimmed;
+use crate::util::conv_p;
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Formatter},
+    path::Path,
+    str::FromStr,
+};
+
+/// Directory with system wide pressure files
+pub const PRESSURE_DIR: &str = "/proc/pressure";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// A single line of a PSI file. Averages are percentages of wall time in which
+/// tasks were stalled, total is the absolute stall time in microseconds.
+pub struct PressureRecord {
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+    pub total: u64,
+}
+
+impl PressureRecord {
+    fn parse(line: &str) -> Result<PressureRecord> {
+        let mut record = PressureRecord::default();
+        for field in line.split_whitespace().skip(1) {
+            let mut kv = field.split('=');
+            let (key, value) = match (kv.next(), kv.next()) {
+                (Some(k), Some(v)) => (k, v),
+                _ => continue,
+            };
+            match key {
+                "avg10" => record.avg10 = parse_value(field, value)?,
+                "avg60" => record.avg60 = parse_value(field, value)?,
+                "avg300" => record.avg300 = parse_value(field, value)?,
+                "total" => record.total = parse_value(field, value)?,
+                _ => {}
+            }
+        }
+        Ok(record)
+    }
+}
+
+fn parse_value<T>(field: &str, value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value
+        .parse::<T>()
+        .map_err(|e| anyhow!("Invalid pressure value `{}` - {}", field, e))
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Pressure stall information of a single resource. `full` is missing for cpu
+/// on older kernels.
+pub struct Pressure {
+    pub some: PressureRecord,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full: Option<PressureRecord>,
+}
+
+impl Pressure {
+    /// Parses content of a PSI file like `/proc/pressure/io` or `io.pressure` of a cgroup
+    pub fn parse(content: &str) -> Result<Pressure> {
+        let mut some = None;
+        let mut full = None;
+        for line in content.lines() {
+            if line.starts_with("some") {
+                some = Some(PressureRecord::parse(line)?);
+            } else if line.starts_with("full") {
+                full = Some(PressureRecord::parse(line)?);
+            }
+        }
+
+        Ok(Pressure {
+            some: some.ok_or_else(|| anyhow!("Missing `some` line in pressure file"))?,
+            full,
+        })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Pressure> {
+        Pressure::parse(&read_trimmed(path)?)
+    }
+}
+
+impl fmt::Display for Pressure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = self.some;
+        write!(f, "some {:.2} {:.2} {:.2}", s.avg10, s.avg60, s.avg300)?;
+        if let Some(full) = self.full {
+            write!(f, " full {:.2} {:.2} {:.2}", full.avg10, full.avg60, full.avg300)?;
+        }
+        Ok(())
+    }
+}
+
+impl Pressure {
+    /// Returns labeled averages of both lines, `full` ones only if available
+    pub fn averages(&self) -> Vec<(&'static str, f64)> {
+        let mut values = vec![
+            ("some avg10", self.some.avg10),
+            ("some avg60", self.some.avg60),
+            ("some avg300", self.some.avg300),
+        ];
+        if let Some(full) = self.full {
+            values.push(("full avg10", full.avg10));
+            values.push(("full avg60", full.avg60));
+            values.push(("full avg300", full.avg300));
+        }
+        values
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// System wide pressure stall information from `/proc/pressure`
+pub struct SystemPressure {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<Pressure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Pressure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub io: Option<Pressure>,
+}
+
+impl SystemPressure {
+    /// Reads all resources from `/proc/pressure`. Fails only if none of them
+    /// is available, for example when the kernel was built without PSI.
+    pub fn read() -> Result<SystemPressure> {
+        let pressure = SystemPressure {
+            cpu: SystemPressure::resource("cpu").ok(),
+            memory: SystemPressure::resource("memory").ok(),
+            io: SystemPressure::resource("io").ok(),
+        };
+        if pressure.cpu.is_none() && pressure.memory.is_none() && pressure.io.is_none() {
+            return Err(anyhow!(
+                "Pressure stall information is not available in `{}`",
+                PRESSURE_DIR
+            ));
+        }
+        Ok(pressure)
+    }
+
+    /// Reads a single resource like `cpu`, `memory` or `io`
+    pub fn resource(name: &str) -> Result<Pressure> {
+        Pressure::from_file(Path::new(PRESSURE_DIR).join(name))
+    }
+
+    pub(crate) fn table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP);
+        table.add_row(row![c => "resource", "some avg10", "avg60", "avg300", "full avg10", "avg60", "avg300",]);
+        for (name, pressure) in &[("cpu", self.cpu), ("memory", self.memory), ("io", self.io)] {
+            if let Some(p) = pressure {
+                let full = |f: fn(&PressureRecord) -> f64| p.full.as_ref().map(f).map(conv_p).unwrap_or_default();
+                table.add_row(row![
+                    name,
+                    r -> conv_p(p.some.avg10),
+                    r -> conv_p(p.some.avg60),
+                    r -> conv_p(p.some.avg300),
+                    r -> full(|r| r.avg10),
+                    r -> full(|r| r.avg60),
+                    r -> full(|r| r.avg300),
+                ]);
+            }
+        }
+        table
+    }
+}
+
+impl fmt::Display for SystemPressure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, " PRESSURE: (share of time tasks stalled)\n{}", self.table())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_some_and_full_lines() {
+        let pressure = Pressure::parse(
+            "some avg10=1.50 avg60=0.75 avg300=0.25 total=123456\nfull avg10=0.50 avg60=0.00 avg300=0.00 total=789",
+        )
+        .unwrap();
+        assert_eq!(
+            pressure.some,
+            PressureRecord {
+                avg10: 1.5,
+                avg60: 0.75,
+                avg300: 0.25,
+                total: 123456,
+            }
+        );
+        assert_eq!(pressure.full.map(|full| full.total), Some(789));
+    }
+
+    #[test]
+    fn full_line_is_optional() {
+        let pressure = Pressure::parse("some avg10=0.00 avg60=0.00 avg300=0.00 total=0").unwrap();
+        assert!(pressure.full.is_none());
+        assert!(Pressure::parse("full avg10=0.00 avg60=0.00 avg300=0.00 total=0").is_err());
+        assert!(Pressure::parse("some avg10=x avg60=0.00 avg300=0.00 total=0").is_err());
+    }
+}
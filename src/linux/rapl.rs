@@ -0,0 +1,183 @@
+use super::{counter::Counter, read_parse, read_trimmed};
+use crate::util::conv_w;
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Formatter},
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+const POWERCAP_DIR: &str = "class/powercap";
+const RAPL_PREFIX: &str = "intel-rapl:";
+
+#[derive(Debug, Clone)]
+/// A single RAPL power domain like a cpu package, its cores or dram. AMD cpus
+/// expose their counters through the same `intel-rapl` interface.
+pub struct RaplDomain {
+    /// Name of the domain prefixed by its package for subdomains, like `package-0/dram`
+    pub name: String,
+    /// Energy counter in microjoules
+    pub energy_uj: u64,
+    /// Value at which the energy counter wraps around, missing on some kernels
+    pub max_energy_range_uj: Option<u64>,
+    path: PathBuf,
+}
+
+impl RaplDomain {
+    fn read(dir: &Path, parent: Option<&str>) -> Result<RaplDomain> {
+        let name = read_trimmed(dir.join("name"))?;
+        let mut domain = RaplDomain {
+            name: match parent {
+                Some(parent) => format!("{}/{}", parent, name),
+                None => name,
+            },
+            energy_uj: 0,
+            max_energy_range_uj: read_parse(dir.join("max_energy_range_uj")),
+            path: dir.to_path_buf(),
+        };
+        domain.update()?;
+        Ok(domain)
+    }
+
+    /// Rereads the energy counter
+    pub fn update(&mut self) -> Result<()> {
+        let path = self.path.join("energy_uj");
+        self.energy_uj = read_trimmed(&path)?
+            .parse::<u64>()
+            .map_err(|e| anyhow!("Invalid energy counter in `{}` - {}", path.display(), e))?;
+        Ok(())
+    }
+
+    /// Returns a counter of consumed energy starting at the current value. It wraps around
+    /// at `max_energy_range_uj` if known, otherwise any drop is reported as a reset.
+    pub fn counter(&self) -> Counter {
+        let counter = Counter::new(self.energy_uj);
+        match self.max_energy_range_uj {
+            Some(max) => counter.with_max(max),
+            None => counter,
+        }
+    }
+}
+
+/// Returns all RAPL domains found in sysfs mounted at `sysfs`, usually `/sys`.
+/// Reading energy counters usually requires root privileges.
+pub fn rapl_domains<P: AsRef<Path>>(sysfs: P) -> Result<Vec<RaplDomain>> {
+    let dir = sysfs.as_ref().join(POWERCAP_DIR);
+    let mut packages = rapl_dirs(&dir)?
+        .into_iter()
+        // Top level zones are `intel-rapl:N`, subzones `intel-rapl:N:M` are nested within them
+        .filter(|p| zone_depth(p) == 1)
+        .collect::<Vec<PathBuf>>();
+    packages.sort();
+
+    let mut domains = Vec::new();
+    for package in packages {
+        let domain = RaplDomain::read(&package, None)?;
+        let parent = domain.name.clone();
+        domains.push(domain);
+
+        let mut subzones = rapl_dirs(&package).unwrap_or_default();
+        subzones.sort();
+        for subzone in subzones {
+            domains.push(RaplDomain::read(&subzone, Some(&parent))?);
+        }
+    }
+
+    if domains.is_empty() {
+        return Err(anyhow!("No RAPL domains found in `{}`", dir.display()));
+    }
+
+    Ok(domains)
+}
+
+fn rapl_dirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    Ok(fs::read_dir(dir)
+        .map_err(|e| anyhow!("Failed to read directory `{}` - {}", dir.display(), e))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .map(|n| n.to_string_lossy().starts_with(RAPL_PREFIX))
+                .unwrap_or(false)
+        })
+        .collect())
+}
+
+fn zone_depth(path: &Path) -> usize {
+    path.file_name()
+        .map(|n| n.to_string_lossy().matches(':').count())
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Average power of a RAPL domain between two reads
+pub struct RaplPower {
+    pub domain: String,
+    pub watts: f64,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct RaplPowers(pub Vec<RaplPower>);
+
+impl fmt::Display for RaplPowers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+        for power in &self.0 {
+            table.add_row(row![format!("{}:", power.domain), r -> conv_w(power.watts)]);
+        }
+        write!(f, " POWER:\n{}", table)
+    }
+}
+
+/// Keeps state of energy counters between samples to calculate power
+pub struct RaplMeter {
+    domains: Vec<(RaplDomain, Counter)>,
+    last: Option<Instant>,
+}
+
+impl RaplMeter {
+    pub fn new<P: AsRef<Path>>(sysfs: P) -> Result<RaplMeter> {
+        Ok(RaplMeter {
+            domains: rapl_domains(sysfs)?
+                .into_iter()
+                .map(|domain| {
+                    let counter = domain.counter();
+                    (domain, counter)
+                })
+                .collect(),
+            last: None,
+        })
+    }
+
+    /// Returns average power of each domain since previous sample. First call
+    /// only records the counters and returns None. Domains whose counter was reset
+    /// are left out.
+    pub fn sample(&mut self) -> Result<Option<RaplPowers>> {
+        let mut deltas = Vec::with_capacity(self.domains.len());
+        for (domain, counter) in &mut self.domains {
+            domain.update()?;
+            deltas.push(counter.update(domain.energy_uj).value());
+        }
+        let elapsed = self.last.map(|l| l.elapsed().as_secs_f64());
+        self.last = Some(Instant::now());
+
+        Ok(elapsed.filter(|e| *e > 0.).map(|elapsed| {
+            RaplPowers(
+                self.domains
+                    .iter()
+                    .zip(deltas)
+                    .filter_map(|((d, _), delta)| {
+                        Some(RaplPower {
+                            domain: d.name.clone(),
+                            watts: delta? as f64 / 1_000_000. / elapsed,
+                        })
+                    })
+                    .collect(),
+            )
+        }))
+    }
+}
@@ -0,0 +1,204 @@
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Formatter},
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+const ROUTE_FILE: &str = "/proc/net/route";
+const IPV6_ROUTE_FILE: &str = "/proc/net/ipv6_route";
+
+// Route flags from `include/uapi/linux/route.h` and `include/uapi/linux/ipv6_route.h`
+// with the letters `route -n` uses for them
+const FLAGS: &[(u32, char)] = &[
+    (0x0001, 'U'),      // RTF_UP
+    (0x0002, 'G'),      // RTF_GATEWAY
+    (0x0004, 'H'),      // RTF_HOST
+    (0x0008, 'R'),      // RTF_REINSTATE
+    (0x0010, 'D'),      // RTF_DYNAMIC
+    (0x0020, 'M'),      // RTF_MODIFIED
+    (0x0200, '!'),      // RTF_REJECT
+    (0x0004_0000, 'A'), // RTF_ADDRCONF
+    (0x0100_0000, 'C'), // RTF_CACHE
+];
+// Routes to addresses of the host itself from the local table
+const RTF_LOCAL: u32 = 0x8000_0000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Route {
+    pub destination: IpAddr,
+    pub prefix_len: u8,
+    /// Next hop, missing for routes to directly connected networks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gateway: Option<IpAddr>,
+    /// Flags as letters like `UG` the same way `route -n` prints them
+    pub flags: String,
+    pub metric: u32,
+    pub iface: String,
+}
+
+impl Route {
+    /// Parses a line of /proc/net/route like:
+    /// `eth0 00000000 0102A8C0 0003 0 0 100 00000000 0 0 0`
+    /// Addresses are 32 bit words in host byte order.
+    pub fn parse_ipv4(line: &str) -> Option<Route> {
+        let elems = line.split_whitespace().collect::<Vec<&str>>();
+        if elems.len() < 8 {
+            return None;
+        }
+        let addr = |s: &str| u32::from_str_radix(s, 16).ok().map(|a| Ipv4Addr::from(a.to_ne_bytes()));
+        let gateway = addr(elems[2])?;
+        let mask = addr(elems[7])?;
+        Some(Route {
+            destination: IpAddr::V4(addr(elems[1])?),
+            prefix_len: u32::from(mask).count_ones() as u8,
+            gateway: if gateway.is_unspecified() {
+                None
+            } else {
+                Some(IpAddr::V4(gateway))
+            },
+            flags: flags_str(u32::from_str_radix(elems[3], 16).ok()?),
+            metric: elems[6].parse().ok()?,
+            iface: elems[0].to_string(),
+        })
+    }
+
+    /// Parses a line of /proc/net/ipv6_route which has destination, destination prefix,
+    /// source, source prefix, gateway, metric, reference count, use count, flags and interface.
+    /// Addresses are in network byte order. Returns None for local routes.
+    pub fn parse_ipv6(line: &str) -> Option<Route> {
+        let elems = line.split_whitespace().collect::<Vec<&str>>();
+        if elems.len() < 10 {
+            return None;
+        }
+        let flags = u32::from_str_radix(elems[8], 16).ok()?;
+        if flags & RTF_LOCAL != 0 {
+            return None;
+        }
+        let addr = |s: &str| u128::from_str_radix(s, 16).ok().map(Ipv6Addr::from);
+        let gateway = addr(elems[4])?;
+        Some(Route {
+            destination: IpAddr::V6(addr(elems[0])?),
+            prefix_len: u8::from_str_radix(elems[1], 16).ok()?,
+            gateway: if gateway.is_unspecified() {
+                None
+            } else {
+                Some(IpAddr::V6(gateway))
+            },
+            flags: flags_str(flags),
+            metric: u32::from_str_radix(elems[5], 16).ok()?,
+            iface: elems[9].to_string(),
+        })
+    }
+
+    /// Destination in CIDR notation like `192.168.1.0/24` or `default`
+    pub fn destination_str(&self) -> String {
+        if self.prefix_len == 0 && self.destination.is_unspecified() {
+            "default".to_string()
+        } else {
+            format!("{}/{}", self.destination, self.prefix_len)
+        }
+    }
+}
+
+fn flags_str(flags: u32) -> String {
+    FLAGS
+        .iter()
+        .filter(|(flag, _)| flags & flag != 0)
+        .map(|(_, c)| c)
+        .collect()
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// IPv4 and IPv6 routes of all tables. Routes to addresses of the host itself are skipped.
+pub struct Routes(pub Vec<Route>);
+
+impl Routes {
+    /// Reads IPv4 and IPv6 routes. IPv6 routes are skipped on kernels without IPv6.
+    pub fn read() -> Result<Routes> {
+        let ipv4 = fs::read_to_string(ROUTE_FILE).map_err(|e| anyhow!("Failed to read `{}` - {}", ROUTE_FILE, e))?;
+        // First line of IPv4 routes is a header
+        let mut routes = ipv4
+            .lines()
+            .skip(1)
+            .filter_map(Route::parse_ipv4)
+            .collect::<Vec<Route>>();
+        if let Ok(ipv6) = fs::read_to_string(IPV6_ROUTE_FILE) {
+            routes.extend(ipv6.lines().filter_map(Route::parse_ipv6));
+        }
+        Ok(Routes(routes))
+    }
+}
+
+impl fmt::Display for Routes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP);
+        table.add_row(row![c => "destination", "gateway", "flags", "metric", "iface",]);
+        for route in &self.0 {
+            table.add_row(row![
+                route.destination_str(),
+                route.gateway.map(|g| g.to_string()).unwrap_or_else(|| "-".to_string()),
+                route.flags,
+                r -> route.metric,
+                route.iface,
+            ]);
+        }
+        write!(f, " ROUTES:\n{}", table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // IPv4 addresses are printed in host byte order so samples are the ones of a little endian host
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn parses_ipv4_routes() {
+        let default = Route::parse_ipv4("eth0 00000000 0102A8C0 0003 0 0 100 00000000 0 0 0").unwrap();
+        assert_eq!(default.destination_str(), "default");
+        assert_eq!(default.gateway, Some("192.168.2.1".parse().unwrap()));
+        assert_eq!(default.flags, "UG");
+        assert_eq!(default.metric, 100);
+        assert_eq!(default.iface, "eth0");
+
+        let link = Route::parse_ipv4("eth0 0002A8C0 00000000 0001 0 0 100 00FFFFFF 0 0 0").unwrap();
+        assert_eq!(link.destination_str(), "192.168.2.0/24");
+        assert_eq!(link.gateway, None);
+        assert_eq!(link.flags, "U");
+    }
+
+    #[test]
+    fn parses_ipv6_routes() {
+        let link = Route::parse_ipv6(
+            "fe800000000000000000000000000000 40 00000000000000000000000000000000 00 \
+             00000000000000000000000000000000 00000100 00000001 00000000 00000001 eth0",
+        )
+        .unwrap();
+        assert_eq!(link.destination_str(), "fe80::/64");
+        assert_eq!(link.gateway, None);
+        assert_eq!(link.metric, 256);
+
+        let default = Route::parse_ipv6(
+            "00000000000000000000000000000000 00 00000000000000000000000000000000 00 \
+             fe800000000000000000000000000001 00000400 00000001 00000000 00000003 eth0",
+        )
+        .unwrap();
+        assert_eq!(default.destination_str(), "default");
+        assert_eq!(default.gateway, Some("fe80::1".parse().unwrap()));
+        assert_eq!(default.flags, "UG");
+        assert_eq!(default.metric, 1024);
+    }
+
+    #[test]
+    fn skips_local_ipv6_routes() {
+        assert!(Route::parse_ipv6(
+            "00000000000000000000000000000001 80 00000000000000000000000000000000 00 \
+             00000000000000000000000000000000 00000000 00000003 00000000 80200001 lo",
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,332 @@
+use super::{read_parse, read_trimmed};
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Formatter},
+    fs,
+    path::{Path, PathBuf},
+};
+
+const HWMON_DIR: &str = "class/hwmon";
+const THERMAL_DIR: &str = "class/thermal";
+const THERMAL_ZONE_PREFIX: &str = "thermal_zone";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SensorKind {
+    Temperature,
+    Fan,
+    Voltage,
+    Power,
+}
+
+impl SensorKind {
+    /// Maps hwmon attribute prefix like `temp` to sensor kind
+    fn from_prefix(prefix: &str) -> Option<SensorKind> {
+        match prefix {
+            "temp" => Some(SensorKind::Temperature),
+            "fan" => Some(SensorKind::Fan),
+            "in" => Some(SensorKind::Voltage),
+            "power" => Some(SensorKind::Power),
+            _ => None,
+        }
+    }
+
+    /// Divisor converting raw sysfs value to base unit
+    fn scale(self) -> f64 {
+        match self {
+            // millidegree Celsius
+            SensorKind::Temperature => 1000.,
+            // RPM
+            SensorKind::Fan => 1.,
+            // millivolts
+            SensorKind::Voltage => 1000.,
+            // microwatts
+            SensorKind::Power => 1_000_000.,
+        }
+    }
+
+    /// Formats value in base unit of this kind
+    pub fn fmt_value(self, value: f64) -> String {
+        match self {
+            SensorKind::Temperature => format!("{:.1}°C", value),
+            SensorKind::Fan => format!("{:.0} RPM", value),
+            SensorKind::Voltage => format!("{:.2} V", value),
+            SensorKind::Power => format!("{:.2} W", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A single reading of a hwmon chip or a thermal zone. Values are in base units,
+/// that is degrees Celsius, RPM, volts or watts.
+pub struct Sensor {
+    /// Name of the chip like `coretemp` or type of the thermal zone
+    pub chip: String,
+    /// Label of the reading if the driver provides one, attribute name otherwise
+    pub label: String,
+    pub kind: SensorKind,
+    pub value: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crit: Option<f64>,
+    pub alarm: bool,
+    #[serde(skip)]
+    input: PathBuf,
+    /// Alarm attributes of the sensor that exist, any of them set raises the alarm
+    #[serde(skip)]
+    alarms: Vec<PathBuf>,
+}
+
+impl Sensor {
+    /// Rereads current value and alarm state of this sensor
+    pub fn update(&mut self) -> Result<()> {
+        let raw = read_trimmed(&self.input)?
+            .parse::<f64>()
+            .map_err(|e| anyhow!("Invalid sensor value in `{}` - {}", self.input.display(), e))?;
+        self.value = raw / self.kind.scale();
+        self.alarm = self
+            .alarms
+            .iter()
+            .any(|path| read_parse::<u8, _>(path).unwrap_or(0) != 0);
+        Ok(())
+    }
+
+    /// Returns full name of the sensor like `coretemp Core 0`
+    pub fn name(&self) -> String {
+        format!("{} {}", self.chip, self.label)
+    }
+
+    /// Returns how close the value is to the critical threshold or max if there is
+    /// no critical one as a ratio. None if the sensor has no thresholds.
+    pub fn threshold_ratio(&self) -> Option<f64> {
+        let limit = self.crit.or(self.max)?;
+        if limit <= 0. {
+            return None;
+        }
+        Some(self.value / limit)
+    }
+
+    fn read_hwmon(dir: &Path, chip: &str, prefix: &str, kind: SensorKind) -> Option<Sensor> {
+        let attr = |name: &str| dir.join(format!("{}_{}", prefix, name));
+        let threshold = |name: &str| read_parse::<f64, _>(attr(name)).map(|v| v / kind.scale());
+
+        let input = if kind == SensorKind::Power && !attr("input").exists() {
+            attr("average")
+        } else {
+            attr("input")
+        };
+        let mut sensor = Sensor {
+            chip: chip.to_string(),
+            label: read_trimmed(attr("label")).unwrap_or_else(|_| prefix.to_string()),
+            kind,
+            value: 0.,
+            max: threshold("max"),
+            crit: threshold("crit"),
+            alarm: false,
+            input,
+            alarms: ["alarm", "crit_alarm", "max_alarm"]
+                .iter()
+                .map(|name| attr(name))
+                .filter(|path| path.exists())
+                .collect(),
+        };
+        sensor.update().ok()?;
+        Some(sensor)
+    }
+
+    fn read_thermal_zone(dir: &Path) -> Option<Sensor> {
+        let zone = dir.file_name()?.to_string_lossy().to_string();
+        let kind = SensorKind::Temperature;
+        let crit = (0..)
+            .map(|i| (dir.join(format!("trip_point_{}_type", i)), i))
+            .take_while(|(path, _)| path.exists())
+            .find(|(path, _)| read_trimmed(path).map(|t| t == "critical").unwrap_or(false))
+            .and_then(|(_, i)| read_parse::<f64, _>(dir.join(format!("trip_point_{}_temp", i))))
+            .map(|v| v / kind.scale());
+
+        let mut sensor = Sensor {
+            chip: read_trimmed(dir.join("type")).unwrap_or_else(|_| zone.clone()),
+            label: zone,
+            kind,
+            value: 0.,
+            max: None,
+            crit,
+            alarm: false,
+            input: dir.join("temp"),
+            alarms: Vec::new(),
+        };
+        sensor.update().ok()?;
+        Some(sensor)
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// All hardware sensors found in hwmon and thermal classes
+pub struct Sensors(pub Vec<Sensor>);
+
+impl Sensors {
+    /// Reads sensors from sysfs mounted at `sysfs`, usually `/sys`
+    pub fn read<P: AsRef<Path>>(sysfs: P) -> Result<Sensors> {
+        let sysfs = sysfs.as_ref();
+        let (hwmon, thermal) = match (list_dir(&sysfs.join(HWMON_DIR)), list_dir(&sysfs.join(THERMAL_DIR))) {
+            (Err(e), Err(_)) => return Err(anyhow!("No hardware sensors available - {}", e)),
+            (hwmon, thermal) => (hwmon.unwrap_or_default(), thermal.unwrap_or_default()),
+        };
+        let mut sensors = Vec::new();
+
+        for dir in hwmon {
+            sensors.extend(read_hwmon_chip(&dir));
+        }
+        for dir in thermal {
+            let is_zone = dir
+                .file_name()
+                .map(|n| n.to_string_lossy().starts_with(THERMAL_ZONE_PREFIX))
+                .unwrap_or(false);
+            if is_zone {
+                sensors.extend(Sensor::read_thermal_zone(&dir));
+            }
+        }
+
+        Ok(Sensors(sensors))
+    }
+}
+
+impl fmt::Display for Sensors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP);
+        table.add_row(row![c => "chip", "sensor", "value", "max", "crit", "alarm",]);
+        for sensor in &self.0 {
+            let threshold = |v: Option<f64>| v.map(|v| sensor.kind.fmt_value(v)).unwrap_or_default();
+            table.add_row(row![
+                sensor.chip,
+                sensor.label,
+                r -> sensor.kind.fmt_value(sensor.value),
+                r -> threshold(sensor.max),
+                r -> threshold(sensor.crit),
+                c -> if sensor.alarm { "ALARM" } else { "" },
+            ]);
+        }
+        write!(f, " SENSORS:\n{}", table)
+    }
+}
+
+/// Reads all readings of a single hwmon chip. Older drivers keep attributes
+/// in `device` subdirectory so it is checked as well.
+fn read_hwmon_chip(dir: &Path) -> Vec<Sensor> {
+    let chip = read_trimmed(dir.join("name"))
+        .or_else(|_| read_trimmed(dir.join("device").join("name")))
+        .unwrap_or_else(|_| {
+            dir.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+
+    let mut sensors = Vec::new();
+    for attr_dir in &[dir.to_path_buf(), dir.join("device")] {
+        let mut inputs = fs::read_dir(attr_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter_map(|e| parse_input_name(&e.file_name().to_string_lossy()))
+                    .collect::<Vec<(String, u32)>>()
+            })
+            .unwrap_or_default();
+        inputs.sort_by(|(p1, i1), (p2, i2)| p1.cmp(p2).then(i1.cmp(i2)));
+        inputs.dedup();
+
+        for (prefix, idx) in inputs {
+            if let Some(kind) = SensorKind::from_prefix(&prefix) {
+                let name = format!("{}{}", prefix, idx);
+                sensors.extend(Sensor::read_hwmon(attr_dir, &chip, &name, kind));
+            }
+        }
+        if !sensors.is_empty() {
+            break;
+        }
+    }
+    sensors
+}
+
+/// Splits attribute name like `temp1_input` into `("temp", 1)`
+fn parse_input_name(name: &str) -> Option<(String, u32)> {
+    let base = name.strip_suffix("_input").or_else(|| name.strip_suffix("_average"))?;
+    let idx_start = base.find(|c: char| c.is_ascii_digit())?;
+    let idx = base[idx_start..].parse::<u32>().ok()?;
+    Some((base[..idx_start].to_string(), idx))
+}
+
+fn list_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(dir)
+        .map_err(|e| anyhow!("Failed to read directory `{}` - {}", dir.display(), e))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .collect::<Vec<PathBuf>>();
+    entries.sort();
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_hwmon_and_thermal_sensors_from_fixture() {
+        let sysfs = std::env::temp_dir().join(format!("rsys-cli-sensors-{}", std::process::id()));
+        let hwmon = sysfs.join(HWMON_DIR).join("hwmon0");
+        let zone = sysfs.join(THERMAL_DIR).join("thermal_zone0");
+        fs::create_dir_all(&hwmon).unwrap();
+        fs::create_dir_all(&zone).unwrap();
+        let write = |path: PathBuf, content: &str| fs::write(path, content).unwrap();
+        write(hwmon.join("name"), "coretemp\n");
+        write(hwmon.join("temp1_input"), "45000\n");
+        write(hwmon.join("temp1_label"), "Core 0\n");
+        write(hwmon.join("temp1_crit"), "100000\n");
+        write(hwmon.join("fan1_input"), "1200\n");
+        write(zone.join("type"), "x86_pkg_temp\n");
+        write(zone.join("temp"), "50000\n");
+        write(zone.join("trip_point_0_type"), "critical\n");
+        write(zone.join("trip_point_0_temp"), "105000\n");
+
+        let sensors = Sensors::read(&sysfs);
+        fs::remove_dir_all(&sysfs).unwrap();
+        let sensors = sensors.unwrap().0;
+
+        assert_eq!(sensors.len(), 3);
+        assert_eq!(sensors[0].name(), "coretemp fan1");
+        assert_eq!(sensors[0].kind, SensorKind::Fan);
+        assert_eq!(sensors[0].value, 1200.);
+        assert_eq!(sensors[1].name(), "coretemp Core 0");
+        assert_eq!(sensors[1].kind, SensorKind::Temperature);
+        assert_eq!(sensors[1].value, 45.);
+        assert_eq!(sensors[1].crit, Some(100.));
+        assert_eq!(sensors[2].name(), "x86_pkg_temp thermal_zone0");
+        assert_eq!(sensors[2].value, 50.);
+        assert_eq!(sensors[2].crit, Some(105.));
+    }
+
+    #[test]
+    fn update_rereads_alarm() {
+        let sysfs = std::env::temp_dir().join(format!("rsys-cli-sensor-alarm-{}", std::process::id()));
+        let hwmon = sysfs.join(HWMON_DIR).join("hwmon0");
+        fs::create_dir_all(&hwmon).unwrap();
+        let write = |name: &str, content: &str| fs::write(hwmon.join(name), content).unwrap();
+        write("name", "nct6775\n");
+        write("in0_input", "1200\n");
+        write("in0_max_alarm", "0\n");
+
+        let mut sensor = Sensors::read(&sysfs)
+            .ok()
+            .and_then(|sensors| sensors.0.into_iter().next());
+        let before = sensor.as_ref().map(|sensor| sensor.alarm);
+        write("in0_max_alarm", "1\n");
+        let updated = sensor.as_mut().map(|sensor| sensor.update().is_ok());
+        fs::remove_dir_all(&sysfs).unwrap();
+
+        assert_eq!(before, Some(false));
+        assert_eq!(updated, Some(true));
+        assert!(sensor.unwrap().alarm);
+    }
+}
@@ -0,0 +1,409 @@
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Formatter},
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    str::FromStr,
+};
+
+const PROC_NET_DIR: &str = "/proc/net";
+// Flag of unix sockets that accept connections
+const UNIX_ACCEPTCON: u32 = 0x10000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SocketProto {
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+    Unix,
+}
+impl SocketProto {
+    pub const ALL: &'static [SocketProto] = &[
+        SocketProto::Tcp,
+        SocketProto::Tcp6,
+        SocketProto::Udp,
+        SocketProto::Udp6,
+        SocketProto::Unix,
+    ];
+
+    /// Name of the file in /proc/net listing sockets of this protocol
+    pub fn file_name(self) -> &'static str {
+        match self {
+            SocketProto::Tcp => "tcp",
+            SocketProto::Tcp6 => "tcp6",
+            SocketProto::Udp => "udp",
+            SocketProto::Udp6 => "udp6",
+            SocketProto::Unix => "unix",
+        }
+    }
+}
+impl fmt::Display for SocketProto {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file_name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// State of a socket. TCP states are the ones from `include/net/tcp_states.h`, UDP sockets
+/// are either `established` if connected or `close`, unix sockets map to the closest TCP state.
+pub enum SocketState {
+    Established,
+    SynSent,
+    SynRecv,
+    FinWait1,
+    FinWait2,
+    TimeWait,
+    Close,
+    CloseWait,
+    LastAck,
+    Listen,
+    Closing,
+    NewSynRecv,
+    Unknown,
+}
+impl SocketState {
+    pub const VARIANTS: &'static [&'static str] = &[
+        "established",
+        "syn-sent",
+        "syn-recv",
+        "fin-wait1",
+        "fin-wait2",
+        "time-wait",
+        "close",
+        "close-wait",
+        "last-ack",
+        "listen",
+        "closing",
+        "new-syn-recv",
+    ];
+    /// States of TCP sockets worth graphing
+    pub const TCP: &'static [SocketState] = &[
+        SocketState::Established,
+        SocketState::Listen,
+        SocketState::SynSent,
+        SocketState::SynRecv,
+        SocketState::FinWait1,
+        SocketState::FinWait2,
+        SocketState::TimeWait,
+        SocketState::CloseWait,
+        SocketState::LastAck,
+        SocketState::Closing,
+    ];
+
+    /// Maps state code from /proc/net/{tcp,udp}
+    fn from_code(code: u8) -> SocketState {
+        match code {
+            0x01 => SocketState::Established,
+            0x02 => SocketState::SynSent,
+            0x03 => SocketState::SynRecv,
+            0x04 => SocketState::FinWait1,
+            0x05 => SocketState::FinWait2,
+            0x06 => SocketState::TimeWait,
+            0x07 => SocketState::Close,
+            0x08 => SocketState::CloseWait,
+            0x09 => SocketState::LastAck,
+            0x0A => SocketState::Listen,
+            0x0B => SocketState::Closing,
+            0x0C => SocketState::NewSynRecv,
+            _ => SocketState::Unknown,
+        }
+    }
+
+    /// Maps flags and `socket_state` of /proc/net/unix
+    fn from_unix(flags: u32, state: u8) -> SocketState {
+        if flags & UNIX_ACCEPTCON != 0 {
+            return SocketState::Listen;
+        }
+        match state {
+            // SS_CONNECTING
+            0x02 => SocketState::SynSent,
+            // SS_CONNECTED
+            0x03 => SocketState::Established,
+            // SS_DISCONNECTING
+            0x04 => SocketState::Closing,
+            _ => SocketState::Close,
+        }
+    }
+}
+impl fmt::Display for SocketState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SocketState::Unknown => "unknown",
+            state => SocketState::VARIANTS[*state as usize],
+        };
+        write!(f, "{}", name)
+    }
+}
+impl FromStr for SocketState {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        SocketState::VARIANTS
+            .iter()
+            .position(|v| *v == s)
+            .map(|i| SocketState::from_code(i as u8 + 1))
+            .ok_or_else(|| anyhow!("Unknown socket state `{}`", s))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A single socket from /proc/net
+pub struct Socket {
+    pub proto: SocketProto,
+    pub state: SocketState,
+    /// Local address of inet sockets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local: Option<SocketAddr>,
+    /// Remote address of inet sockets, unspecified if not connected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<SocketAddr>,
+    /// Bound path of unix sockets. Abstract sockets start with `@`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Bytes in send queue
+    pub tx_queue: u64,
+    /// Bytes in receive queue
+    pub rx_queue: u64,
+    /// Owner of inet sockets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uid: Option<u32>,
+    pub inode: u64,
+}
+
+impl Socket {
+    // Parses a line of /proc/net/{tcp,tcp6,udp,udp6} like:
+    // `0: 0100007F:0277 00000000:0000 0A 00000000:00000000 00:00000000 00000000 0 0 23570 ...`
+    fn parse_inet(proto: SocketProto, line: &str) -> Option<Socket> {
+        let elems = line.split_whitespace().collect::<Vec<&str>>();
+        if elems.len() < 10 {
+            return None;
+        }
+        let mut queues = elems[4].split(':');
+        Some(Socket {
+            proto,
+            state: SocketState::from_code(u8::from_str_radix(elems[3], 16).ok()?),
+            local: Some(parse_addr(elems[1])?),
+            remote: Some(parse_addr(elems[2])?),
+            path: None,
+            tx_queue: u64::from_str_radix(queues.next()?, 16).ok()?,
+            rx_queue: u64::from_str_radix(queues.next()?, 16).ok()?,
+            uid: elems[7].parse::<u32>().ok(),
+            inode: elems[9].parse::<u64>().ok()?,
+        })
+    }
+
+    // Parses a line of /proc/net/unix like:
+    // `0000000000000000: 00000002 00000000 00010000 0001 01 23571 /run/systemd/notify`
+    fn parse_unix(line: &str) -> Option<Socket> {
+        let elems = line.split_whitespace().collect::<Vec<&str>>();
+        if elems.len() < 7 {
+            return None;
+        }
+        let flags = u32::from_str_radix(elems[3], 16).ok()?;
+        let state = u8::from_str_radix(elems[5], 16).ok()?;
+        Some(Socket {
+            proto: SocketProto::Unix,
+            state: SocketState::from_unix(flags, state),
+            local: None,
+            remote: None,
+            path: elems.get(7).map(|p| p.to_string()),
+            tx_queue: 0,
+            rx_queue: 0,
+            uid: None,
+            inode: elems[6].parse::<u64>().ok()?,
+        })
+    }
+
+    /// Whether local or remote port of this socket is `port`
+    pub fn has_port(&self, port: u16) -> bool {
+        self.local.map(|a| a.port()) == Some(port) || self.remote.map(|a| a.port()) == Some(port)
+    }
+
+    fn local_str(&self) -> String {
+        match (&self.local, &self.path) {
+            (Some(addr), _) => addr.to_string(),
+            (None, Some(path)) => path.clone(),
+            (None, None) => "*".to_string(),
+        }
+    }
+
+    fn remote_str(&self) -> String {
+        match &self.remote {
+            Some(addr) if addr.ip().is_unspecified() && addr.port() == 0 => "*".to_string(),
+            Some(addr) => addr.to_string(),
+            None => "*".to_string(),
+        }
+    }
+}
+
+// Parses an address like `0100007F:0016` or a 32 digit IPv6 one. The address is printed
+// by the kernel as 32 bit words in host byte order, the port is in host byte order.
+fn parse_addr(s: &str) -> Option<SocketAddr> {
+    let (addr, port) = s.split_at(s.find(':')?);
+    let port = u16::from_str_radix(&port[1..], 16).ok()?;
+    let mut words = Vec::with_capacity(4);
+    for i in (0..addr.len()).step_by(8) {
+        words.push(u32::from_str_radix(addr.get(i..i + 8)?, 16).ok()?);
+    }
+    let ip = match words.as_slice() {
+        [word] => IpAddr::V4(Ipv4Addr::from(word.to_ne_bytes())),
+        [_, _, _, _] => {
+            let mut octets = [0u8; 16];
+            for (i, word) in words.iter().enumerate() {
+                octets[i * 4..i * 4 + 4].copy_from_slice(&word.to_ne_bytes());
+            }
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Sockets(pub Vec<Socket>);
+
+impl Sockets {
+    /// Reads sockets of all protocols. Protocols without a file in /proc/net, like
+    /// tcp6 on a kernel without IPv6, are skipped.
+    pub fn read() -> Result<Sockets> {
+        Sockets::read_protos(SocketProto::ALL)
+    }
+
+    /// Reads TCP sockets of both IPv4 and IPv6
+    pub fn read_tcp() -> Result<Sockets> {
+        Sockets::read_protos(&[SocketProto::Tcp, SocketProto::Tcp6])
+    }
+
+    fn read_protos(protos: &[SocketProto]) -> Result<Sockets> {
+        let mut sockets = Vec::new();
+        let mut found = false;
+        for proto in protos {
+            let path = format!("{}/{}", PROC_NET_DIR, proto.file_name());
+            if let Ok(content) = fs::read_to_string(&path) {
+                found = true;
+                sockets.extend(Sockets::parse(*proto, &content).0);
+            }
+        }
+        if !found {
+            return Err(anyhow!("Failed to read sockets from `{}`", PROC_NET_DIR));
+        }
+        Ok(Sockets(sockets))
+    }
+
+    /// Parses content of a /proc/net file of `proto` skipping the header
+    pub fn parse(proto: SocketProto, content: &str) -> Sockets {
+        Sockets(
+            content
+                .lines()
+                .skip(1)
+                .filter_map(|line| match proto {
+                    SocketProto::Unix => Socket::parse_unix(line),
+                    _ => Socket::parse_inet(proto, line),
+                })
+                .collect(),
+        )
+    }
+
+    /// Keeps only sockets in `state` and with `port` as local or remote port
+    pub fn filter(self, state: Option<SocketState>, port: Option<u16>) -> Sockets {
+        Sockets(
+            self.0
+                .into_iter()
+                .filter(|s| state.map_or(true, |state| s.state == state))
+                .filter(|s| port.map_or(true, |port| s.has_port(port)))
+                .collect(),
+        )
+    }
+
+    /// Returns number of sockets in each state
+    pub fn count_by_state(&self) -> BTreeMap<SocketState, usize> {
+        let mut counts = BTreeMap::new();
+        for socket in &self.0 {
+            *counts.entry(socket.state).or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+impl fmt::Display for Sockets {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP);
+        table.add_row(row![c => "proto", "state", "recv-q", "send-q", "local", "remote", "uid", "inode",]);
+        for s in &self.0 {
+            table.add_row(row![
+                s.proto,
+                s.state,
+                r -> s.rx_queue,
+                r -> s.tx_queue,
+                s.local_str(),
+                s.remote_str(),
+                r -> s.uid.map(|u| u.to_string()).unwrap_or_default(),
+                r -> s.inode,
+            ]);
+        }
+        write!(f, " SOCKETS:\n{}", table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Addresses are printed in host byte order so samples are the ones of a little endian host
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn parses_ipv4_sockets() {
+        let content = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0277 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 23570 1 0000000000000000 100 0 0 10 0
+   1: 0F02000A:0016 0102000A:C350 01 00000010:00000020 02:00000A3C 00000000  1000        0 31337 4 0000000000000000 20 4 30 10 -1
+";
+        let sockets = Sockets::parse(SocketProto::Tcp, content).0;
+        assert_eq!(sockets.len(), 2);
+        assert_eq!(sockets[0].state, SocketState::Listen);
+        assert_eq!(sockets[0].local, Some("127.0.0.1:631".parse().unwrap()));
+        assert_eq!(sockets[0].remote, Some("0.0.0.0:0".parse().unwrap()));
+        assert_eq!(sockets[0].inode, 23570);
+
+        assert_eq!(sockets[1].state, SocketState::Established);
+        assert_eq!(sockets[1].local, Some("10.0.2.15:22".parse().unwrap()));
+        assert_eq!(sockets[1].remote, Some("10.0.2.1:50000".parse().unwrap()));
+        assert_eq!((sockets[1].tx_queue, sockets[1].rx_queue), (16, 32));
+        assert_eq!(sockets[1].uid, Some(1000));
+        assert!(sockets[1].has_port(22));
+    }
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn parses_ipv6_addresses() {
+        assert_eq!(
+            parse_addr("00000000000000000000000001000000:0016"),
+            Some("[::1]:22".parse().unwrap())
+        );
+        assert_eq!(
+            parse_addr("000080FE000000000000000001000000:01BB"),
+            Some("[fe80::1]:443".parse().unwrap())
+        );
+        assert_eq!(parse_addr("0100007F"), None);
+        assert_eq!(parse_addr("0100007F00:0016"), None);
+    }
+
+    #[test]
+    fn parses_unix_sockets() {
+        let content = "Num       RefCount Protocol Flags    Type St Inode Path
+0000000000000000: 00000002 00000000 00010000 0001 01 23571 /run/systemd/notify
+0000000000000000: 00000003 00000000 00000000 0001 03 23572
+";
+        let sockets = Sockets::parse(SocketProto::Unix, content).0;
+        assert_eq!(sockets.len(), 2);
+        assert_eq!(sockets[0].state, SocketState::Listen);
+        assert_eq!(sockets[0].path.as_deref(), Some("/run/systemd/notify"));
+        assert_eq!(sockets[1].state, SocketState::Established);
+        assert_eq!(sockets[1].path, None);
+    }
+}
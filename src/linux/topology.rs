@@ -0,0 +1,181 @@
+use super::{
+    link::{interface_kind, InterfaceKind, CLASS_NET_DIR},
+    read_parse, read_trimmed,
+};
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::{self, Formatter},
+    fs,
+    path::Path,
+};
+
+/// Guards against cycles in upper and lower links which should never happen but would recurse forever
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A network interface with the interfaces it is built from like ports of a bridge,
+/// slaves of a bond or the parent device of a vlan. Interfaces used by several others,
+/// like a device with multiple vlans, appear under each of them.
+pub struct NetNode {
+    pub name: String,
+    pub kind: InterfaceKind,
+    pub operstate: String,
+    /// Relation to the parent node like `port` of a bridge, `slave` or `active slave`
+    /// of a bond and `parent` of a vlan
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// Other end of a veth pair. Peers in another network namespace are shown
+    /// by their interface index like `if12`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<NetNode>,
+}
+
+impl NetNode {
+    fn read(net_dir: &Path, name: &str, role: Option<String>, indexes: &HashMap<u32, String>, depth: usize) -> NetNode {
+        let dir = net_dir.join(name);
+        let kind = interface_kind(&dir);
+
+        let mut node = NetNode {
+            name: name.to_string(),
+            operstate: read_trimmed(dir.join("operstate")).unwrap_or_else(|_| "unknown".to_string()),
+            role,
+            peer: if kind == InterfaceKind::Veth {
+                veth_peer(net_dir, &dir, indexes)
+            } else {
+                None
+            },
+            children: Vec::new(),
+            kind,
+        };
+
+        if depth < MAX_DEPTH {
+            let active = read_trimmed(dir.join("bonding/active_slave")).ok();
+            node.children = lower_devices(&dir)
+                .into_iter()
+                .map(|lower| {
+                    let role = match kind {
+                        InterfaceKind::Bridge => "port",
+                        InterfaceKind::Bond if active.as_deref() == Some(lower.as_str()) => "active slave",
+                        InterfaceKind::Bond => "slave",
+                        InterfaceKind::Vlan => "parent",
+                        _ => "lower",
+                    };
+                    NetNode::read(net_dir, &lower, Some(role.to_string()), indexes, depth + 1)
+                })
+                .collect();
+        }
+
+        node
+    }
+
+    /// Returns this node and all of its descendants in depth first order together with
+    /// a prefix drawing the branches of the tree.
+    pub fn flatten(&self) -> Vec<(String, &NetNode)> {
+        let mut nodes = vec![(String::new(), self)];
+        self.flatten_children("", &mut nodes);
+        nodes
+    }
+
+    fn flatten_children<'a>(&'a self, indent: &str, nodes: &mut Vec<(String, &'a NetNode)>) {
+        let count = self.children.len();
+        for (i, child) in self.children.iter().enumerate() {
+            let last = i + 1 == count;
+            nodes.push((format!("{}{}", indent, if last { "└─" } else { "├─" }), child));
+            child.flatten_children(&format!("{}{}", indent, if last { "  " } else { "│ " }), nodes);
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// Topology of network interfaces starting from the ones no other interface is built on top of
+pub struct NetTopology(pub Vec<NetNode>);
+
+impl NetTopology {
+    /// Reads interface topology from sysfs mounted at `sysfs`, usually `/sys`
+    pub fn read<P: AsRef<Path>>(sysfs: P) -> Result<NetTopology> {
+        let dir = sysfs.as_ref().join(CLASS_NET_DIR);
+        if !dir.is_dir() {
+            return Err(anyhow!("Directory `{}` doesn't exist", dir.display()));
+        }
+
+        let mut names = entry_names(&dir);
+        names.sort();
+        let indexes = names
+            .iter()
+            .filter_map(|name| read_parse::<u32, _>(dir.join(name).join("ifindex")).map(|i| (i, name.clone())))
+            .collect::<HashMap<u32, String>>();
+
+        Ok(NetTopology(
+            names
+                .iter()
+                .filter(|name| {
+                    let iface = dir.join(name);
+                    !iface.join("master").exists() && !entry_names(&iface).iter().any(|e| e.starts_with("upper_"))
+                })
+                .map(|name| NetNode::read(&dir, name, None, &indexes, 0))
+                .collect(),
+        ))
+    }
+}
+
+// Returns names of interfaces `dir` is built from. Upper devices link to their lower
+// devices with `lower_<name>`, bridges and bonds also list them in `brif` and `bonding/slaves`.
+fn lower_devices(dir: &Path) -> BTreeSet<String> {
+    let mut lowers = entry_names(dir)
+        .into_iter()
+        .filter_map(|e| e.strip_prefix("lower_").map(|name| name.to_string()))
+        .collect::<BTreeSet<String>>();
+    lowers.extend(entry_names(&dir.join("brif")));
+    if let Ok(slaves) = read_trimmed(dir.join("bonding/slaves")) {
+        lowers.extend(slaves.split_whitespace().map(|s| s.to_string()));
+    }
+    lowers
+}
+
+// `iflink` of a veth is the index of its peer. Indexes are only unique within a network
+// namespace so a local interface is only a peer if it points back at this one.
+fn veth_peer(net_dir: &Path, dir: &Path, indexes: &HashMap<u32, String>) -> Option<String> {
+    let ifindex = read_parse::<u32, _>(dir.join("ifindex"))?;
+    let iflink = read_parse::<u32, _>(dir.join("iflink"))?;
+    match indexes.get(&iflink) {
+        Some(peer) if read_parse::<u32, _>(net_dir.join(peer).join("iflink")) == Some(ifindex) => Some(peer.clone()),
+        _ => Some(format!("if{}", iflink)),
+    }
+}
+
+fn entry_names(dir: &Path) -> Vec<String> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl fmt::Display for NetTopology {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_CLEAN);
+        table.add_row(row![c => "name", "type", "state", "role", "peer",]);
+        let opt = |v: &Option<String>| v.clone().unwrap_or_default();
+        for root in &self.0 {
+            for (prefix, node) in root.flatten() {
+                table.add_row(row![
+                    format!("{}{}", prefix, node.name),
+                    node.kind,
+                    node.operstate,
+                    opt(&node.role),
+                    opt(&node.peer),
+                ]);
+            }
+        }
+        write!(f, " NET TOPOLOGY:\n{}", table)
+    }
+}
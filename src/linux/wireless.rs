@@ -0,0 +1,213 @@
+use super::link::CLASS_NET_DIR;
+use anyhow::{anyhow, Result};
+use prettytable::{format, Table};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Formatter},
+    fs,
+    path::Path,
+};
+
+const WIRELESS_FILE: &str = "/proc/net/wireless";
+// Noise level of drivers that don't measure it
+const NOISE_UNKNOWN: f64 = -256.;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Packets discarded by the wireless stack since the interface came up
+pub struct DiscardedPackets {
+    /// Packets for another network id
+    pub nwid: u64,
+    /// Packets that failed to decrypt
+    pub crypt: u64,
+    /// Packets that couldn't be reassembled
+    pub frag: u64,
+    /// Packets that failed to transmit after maximum retries
+    pub retry: u64,
+    pub misc: u64,
+}
+impl DiscardedPackets {
+    pub fn total(&self) -> u64 {
+        self.nwid + self.crypt + self.frag + self.retry + self.misc
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Link quality and signal of a wireless interface from /proc/net/wireless
+pub struct Wireless {
+    pub iface: String,
+    /// Driver specific link quality, out of 70 for most cfg80211 drivers
+    pub link_quality: f64,
+    /// Signal level in dBm
+    pub signal_dbm: f64,
+    /// Noise level in dBm, missing if the driver doesn't measure it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub noise_dbm: Option<f64>,
+    pub discarded: DiscardedPackets,
+    pub missed_beacons: u64,
+    /// Wireless hardware the interface belongs to like `phy0`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phy: Option<String>,
+}
+
+impl Wireless {
+    /// Reads statistics of wireless interface `name`. Fails if the interface is
+    /// not wireless or is down.
+    pub fn read<P: AsRef<Path>>(sysfs: P, name: &str) -> Result<Wireless> {
+        WirelessInterfaces::read(sysfs)?
+            .0
+            .into_iter()
+            .find(|w| w.iface == name)
+            .ok_or_else(|| anyhow!("Interface `{}` is not an active wireless interface", name))
+    }
+
+    /// Parses a line of /proc/net/wireless like:
+    /// `wlan0: 0000   70.  -40.  -256        0      0      0      0      0        0`
+    /// Values followed by a dot were updated since they were last read.
+    pub fn parse(line: &str) -> Option<Wireless> {
+        let (iface, values) = line.split_at(line.find(':')?);
+        let values = values[1..]
+            .split_whitespace()
+            .map(|v| v.trim_end_matches('.'))
+            .collect::<Vec<&str>>();
+        if values.len() < 10 {
+            return None;
+        }
+        let num = |i: usize| values[i].parse::<f64>().ok();
+        let count = |i: usize| values[i].parse::<u64>().ok();
+
+        let mut signal_dbm = num(2)?;
+        // Old drivers report dBm as an unsigned byte
+        if signal_dbm > 0. {
+            signal_dbm -= 256.;
+        }
+        let noise_dbm = num(3)?;
+        Some(Wireless {
+            iface: iface.trim().to_string(),
+            link_quality: num(1)?,
+            signal_dbm,
+            noise_dbm: if noise_dbm <= NOISE_UNKNOWN {
+                None
+            } else {
+                Some(noise_dbm)
+            },
+            discarded: DiscardedPackets {
+                nwid: count(4)?,
+                crypt: count(5)?,
+                frag: count(6)?,
+                retry: count(7)?,
+                misc: count(8)?,
+            },
+            missed_beacons: count(9)?,
+            phy: None,
+        })
+    }
+
+    pub(crate) fn add_rows(&self, table: &mut Table) {
+        table.add_row(row!["link quality:", l -> format!("{:.0}", self.link_quality)]);
+        table.add_row(row!["signal:", l -> format!("{:.0} dBm", self.signal_dbm)]);
+        table.add_row(row![
+            "noise:",
+            l -> self.noise_dbm.map(|n| format!("{:.0} dBm", n)).unwrap_or_else(|| "-".to_string())
+        ]);
+        table.add_row(row!["discarded:", l -> self.discarded.total()]);
+        table.add_row(row!["missed beacons:", l -> self.missed_beacons]);
+        table.add_row(row!["phy:", l -> self.phy.clone().unwrap_or_else(|| "-".to_string())]);
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// All wireless interfaces that are up
+pub struct WirelessInterfaces(pub Vec<Wireless>);
+
+impl WirelessInterfaces {
+    /// Reads statistics of wireless interfaces and their phy from sysfs mounted at
+    /// `sysfs`, usually `/sys`. Returns no interfaces if the kernel has no wireless support.
+    pub fn read<P: AsRef<Path>>(sysfs: P) -> Result<WirelessInterfaces> {
+        let content = match fs::read_to_string(WIRELESS_FILE) {
+            Ok(content) => content,
+            Err(_) if !Path::new(WIRELESS_FILE).exists() => return Ok(WirelessInterfaces::default()),
+            Err(e) => return Err(anyhow!("Failed to read `{}` - {}", WIRELESS_FILE, e)),
+        };
+        let net_dir = sysfs.as_ref().join(CLASS_NET_DIR);
+
+        let mut interfaces = WirelessInterfaces::parse(&content);
+        for wireless in &mut interfaces.0 {
+            wireless.phy = fs::read_link(net_dir.join(&wireless.iface).join("phy80211"))
+                .ok()
+                .and_then(|link| link.file_name().map(|n| n.to_string_lossy().to_string()));
+        }
+        Ok(interfaces)
+    }
+
+    /// Parses /proc/net/wireless skipping its two header lines
+    pub fn parse(content: &str) -> WirelessInterfaces {
+        WirelessInterfaces(content.lines().skip(2).filter_map(Wireless::parse).collect())
+    }
+}
+
+impl fmt::Display for Wireless {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+        self.add_rows(&mut table);
+        write!(f, "{}", table)
+    }
+}
+
+impl fmt::Display for WirelessInterfaces {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_NO_LINESEP);
+        table.add_row(row![c => "name", "quality", "signal", "noise", "discarded", "missed beacons", "phy",]);
+        for w in &self.0 {
+            table.add_row(row![
+                w.iface,
+                r -> format!("{:.0}", w.link_quality),
+                r -> format!("{:.0} dBm", w.signal_dbm),
+                r -> w.noise_dbm.map(|n| format!("{:.0} dBm", n)).unwrap_or_else(|| "-".to_string()),
+                r -> w.discarded.total(),
+                r -> w.missed_beacons,
+                w.phy.clone().unwrap_or_else(|| "-".to_string()),
+            ]);
+        }
+        write!(f, " WIRELESS:\n{}", table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_wireless_interfaces() {
+        let interfaces = WirelessInterfaces::parse(
+            "Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE
+ face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22
+ wlan0: 0000   70.  -40.  -256        0      0      0      0      0        0
+ wlan1: 0000   54.  216.  -95.       1      2      3      4      5        6
+",
+        )
+        .0;
+        assert_eq!(interfaces.len(), 2);
+        let wlan0 = &interfaces[0];
+        assert_eq!(wlan0.iface, "wlan0");
+        assert_eq!(wlan0.link_quality, 70.);
+        assert_eq!(wlan0.signal_dbm, -40.);
+        assert_eq!(wlan0.noise_dbm, None);
+        assert_eq!(wlan0.discarded.total(), 0);
+
+        // Old drivers report the signal as an unsigned byte
+        let wlan1 = &interfaces[1];
+        assert_eq!(wlan1.signal_dbm, -40.);
+        assert_eq!(wlan1.noise_dbm, Some(-95.));
+        assert_eq!(wlan1.discarded.retry, 4);
+        assert_eq!(wlan1.discarded.total(), 15);
+        assert_eq!(wlan1.missed_beacons, 6);
+    }
+
+    #[test]
+    fn rejects_incomplete_lines() {
+        assert!(Wireless::parse("wlan0: 0000 70. -40.").is_none());
+        assert!(Wireless::parse("no interface here").is_none());
+    }
+}
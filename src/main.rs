@@ -2,6 +2,7 @@
 extern crate prettytable;
 pub mod cli;
 pub mod cmd;
+pub mod linux;
 pub mod util;
 use cli::RsysCli;
 
@@ -9,5 +10,6 @@ fn main() {
     let rsys = RsysCli::new();
     if let Err(e) = rsys.main() {
         eprintln!("Error: {}", e);
+        std::process::exit(1);
     }
 }
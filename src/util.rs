@@ -1,3 +1,4 @@
+use anyhow::anyhow;
 use rand::seq::IteratorRandom;
 use rsys::{Error, Result};
 use serde::Serialize;
@@ -5,12 +6,48 @@ use serde_json as json;
 use serde_yaml as yaml;
 use std::any::type_name;
 use std::fmt::{Debug, Display};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tui::style::Color;
 
 const KILO: f64 = 1000.;
-const MEGA: f64 = KILO * KILO;
-const GIGA: f64 = KILO * KILO * KILO;
-const TERA: f64 = KILO * KILO * KILO * KILO;
+const KIBI: f64 = 1024.;
+const SI_PREFIXES: [&str; 5] = ["", "K", "M", "G", "T"];
+const IEC_PREFIXES: [&str; 5] = ["", "Ki", "Mi", "Gi", "Ti"];
+
+// Set from command line options before anything is displayed
+static IEC_UNITS: AtomicBool = AtomicBool::new(false);
+static BIT_RATES: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Prefixes of byte units
+pub enum UnitSystem {
+    /// Powers of 1000 like `MB`
+    Si,
+    /// Powers of 1024 like `MiB` the same as `free -h` uses
+    Iec,
+}
+impl UnitSystem {
+    pub const VARIANTS: &'static [&'static str] = &["si", "iec"];
+}
+impl FromStr for UnitSystem {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "si" => Ok(UnitSystem::Si),
+            "iec" => Ok(UnitSystem::Iec),
+            _ => Err(anyhow!("Unknown unit system `{}`", s)),
+        }
+    }
+}
+
+/// Sets units used by byte conversions. With `bits` rates are displayed in bits
+/// per second so they can be compared with link speeds. Serialized output is not
+/// affected as it always contains raw numbers.
+pub fn set_units(system: UnitSystem, bits: bool) {
+    IEC_UNITS.store(system == UnitSystem::Iec, Ordering::Relaxed);
+    BIT_RATES.store(bits, Ordering::Relaxed);
+}
 
 pub enum PrintFormat {
     Normal,
@@ -63,7 +100,7 @@ pub fn print<T: Debug + Display + Serialize>(val: T, format: PrintFormat, pretty
     Ok(())
 }
 
-pub fn handle_err<T: Default>(res: Result<T>) -> T {
+pub fn handle_err<T: Default, E: Display>(res: std::result::Result<T, E>) -> T {
     match res {
         Ok(val) => val,
         Err(e) => {
@@ -73,28 +110,46 @@ pub fn handle_err<T: Default>(res: Result<T>) -> T {
     }
 }
 
+fn conv_scaled(value: f64, base: f64, prefixes: &[&str], unit: &str) -> String {
+    let mut val = value;
+    let mut i = 0;
+    while val >= base && i + 1 < prefixes.len() {
+        val /= base;
+        i += 1;
+    }
+
+    format!("{:.2}{}{}", val, prefixes[i], unit)
+}
+
 fn conv_metric(value: f64, unit: &str) -> String {
-    let (val, u) = if value < KILO {
-        (value, "")
-    } else if KILO <= value && value < MEGA {
-        (value / KILO, "K")
-    } else if MEGA <= value && value < GIGA {
-        (value / MEGA, "M")
-    } else if GIGA <= value && value < TERA {
-        (value / GIGA, "G")
-    } else {
-        (value / TERA, "T")
-    };
+    conv_scaled(value, KILO, &SI_PREFIXES, unit)
+}
 
-    format!("{:.2}{}{}", val, u, unit)
+// Scales a byte based value with prefixes of the selected unit system
+fn conv_bytes(value: f64, unit: &str) -> String {
+    if IEC_UNITS.load(Ordering::Relaxed) {
+        conv_scaled(value, KIBI, &IEC_PREFIXES, unit)
+    } else {
+        conv_metric(value, unit)
+    }
 }
 
 pub fn conv_fbs(bytes: f64) -> String {
-    conv_metric(bytes, "B/s")
+    conv_bytes(bytes, "B/s")
+}
+
+/// Converts a network transfer rate, in bits per second if selected so that it
+/// can be compared with link speed
+pub fn conv_net_fbs(bytes: f64) -> String {
+    if BIT_RATES.load(Ordering::Relaxed) {
+        conv_bytes(bytes * 8., "bit/s")
+    } else {
+        conv_fbs(bytes)
+    }
 }
 
 pub fn conv_fb(bytes: f64) -> String {
-    conv_metric(bytes, "B")
+    conv_bytes(bytes, "B")
 }
 
 pub fn conv_b(bytes: u64) -> String {
@@ -109,6 +164,10 @@ pub fn conv_fhz(hz: f64) -> String {
     conv_metric(hz, "Hz")
 }
 
+pub fn conv_w(watts: f64) -> String {
+    format!("{:.2}W", watts)
+}
+
 pub fn conv_t(time: f64) -> String {
     format!("{:.1}s", time)
 }
@@ -117,6 +176,59 @@ pub fn conv_p(val: f64) -> String {
     format!("{:.1}%", val)
 }
 
+/// Returns the candidate closest to `name` if it's similar enough to be a likely typo
+pub fn did_you_mean<'a, I: IntoIterator<Item = &'a str>>(name: &str, candidates: I) -> Option<&'a str> {
+    let max_distance = (name.len() / 3).max(2);
+    candidates
+        .into_iter()
+        .map(|c| (levenshtein(name, c), c))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, c)| c)
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut row = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { prev } else { prev + 1 };
+            prev = row[j + 1];
+            row[j + 1] = cost.min(row[j] + 1).min(prev + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Matches `name` against a shell style pattern where `*` matches any sequence of
+/// characters and `?` matches a single character
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let name = name.chars().collect::<Vec<char>>();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and of the name where it started matching
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            // Let the last `*` swallow one more character and retry
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
 pub fn random_color(min: Option<u8>) -> Color {
     let mut rng = rand::thread_rng();
     let mut color: [u8; 3] = [0, 0, 0];